@@ -12,5 +12,40 @@ pub mod admin_wallet {
 
 pub const ED25519_PROGRAM_ID: &str = "Ed25519SigVerify111111111111111111111111111";
 
+// Every off-chain signed message starts with the domain tag and the message format version
+pub const SIGNED_MESSAGE_DOMAIN: &[u8; 11] = b"sol_factory";
+pub const SIGNED_MESSAGE_VERSION: u8 = 1;
+
+pub const MAX_SALE_PHASES: usize = 5;
+
+pub const BPS_DENOMINATOR: u64 = 10000;
+
+// Longest delivery window a collection can set, one year in seconds
+pub const MAX_DELIVERY_WINDOW: i64 = 365 * 24 * 60 * 60;
+
+// Standardized metadata keys of the generation provenance
+pub const MODEL_METADATA_KEY: &str = "model";
+pub const SEED_METADATA_KEY: &str = "seed";
+pub const PROMPT_HASH_METADATA_KEY: &str = "prompt_sha256";
+pub const IMAGE_HASH_METADATA_KEY: &str = "image_sha256";
+pub const PROVENANCE_METADATA_KEYS: [&str; 4] = [MODEL_METADATA_KEY, SEED_METADATA_KEY, PROMPT_HASH_METADATA_KEY, IMAGE_HASH_METADATA_KEY];
+
+// Attributes stored on an AiNft, every one also costs an update_field CPI when the AiNft is minted
+pub const MAX_NFT_ATTRIBUTES: usize = 16;
+
+pub const MAX_BANNED_TERMS: usize = 64;
+pub const MAX_BANNED_TERM_LEN: usize = 32;
+
+// Trait schema bounds, a full schema stays under the 10KB account creation limit
+pub const MAX_SCHEMA_TRAITS: usize = MAX_NFT_ATTRIBUTES;
+pub const MAX_TRAIT_KEY_LEN: usize = 32;
+pub const MAX_TRAIT_VALUES: usize = 16;
+pub const MAX_TRAIT_VALUE_LEN: usize = 32;
+
+// Distinct (key, value) pairs counted per collection for the rarity score
+pub const MAX_TRAIT_COUNTERS: usize = 200;
+// The rarity score is a fixed point number with 3 decimals
+pub const RARITY_SCORE_PRECISION: u64 = 1_000;
+
 pub const ADMIN_FEE: u64 = 100000000; // 0.3 SOL
 // pub const ADMIN_PERCENTAGE: f32 = 0.3;
\ No newline at end of file
@@ -12,29 +12,19 @@ use {
         token::Token,  
         token_interface::{MintTo, mint_to, set_authority, SetAuthority}
     },
-    solana_program::{
-        // system_instruction, 
-        // program::invoke,
-        sysvar::instructions::{
-            self,
-            load_current_index_checked,
-            load_instruction_at_checked
-        }
-    },
+    solana_program::sysvar::instructions,
 };
-use std::str::FromStr;
 use crate::{
-    constant::{
-        self, ED25519_PROGRAM_ID
-        // ADMIN_FEE
-    }, errors::{BuyingError, ProtocolError}, state::{Collection, Placeholder, Protocol}
+    constant, ed25519::{SignedPayload, verify_signed_payload_by_any}, errors::{BuyingError, ProtocolError}, state::{Collection, GenerationQueue, GenerationRequest, MintRecord, Placeholder, Protocol, Voucher}
 };
 
 #[derive(Accounts)]
+#[instruction(expiry: i64, nonce: u64)]
 pub struct AirdropPlaceholder<'info> {
-    /// CHECK: Buyer is being added by the collection owner
+    /// CHECK: Buyer is bound by the signed voucher
     #[account(mut)]
     pub buyer: AccountInfo<'info>,
+    /// the admin wallet, the collection owner, or the buyer when they claim the voucher themselves
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut)]
@@ -58,8 +48,41 @@ pub struct AirdropPlaceholder<'info> {
         mut,
         seeds = [b"placeholder", placeholder.collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
         bump,
+        constraint = placeholder.collection == collection.key() @ BuyingError::CollectionMismatch,
     )] 
     pub placeholder: Account<'info, Placeholder>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MintRecord::INIT_SPACE,
+        seeds = [b"mint_record", collection.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub mint_record: Account<'info, MintRecord>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Voucher::INIT_SPACE,
+        seeds = [b"voucher", collection.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub voucher: Box<Account<'info, Voucher>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = GenerationQueue::INIT_SPACE,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        init,
+        payer = payer,
+        space = GenerationRequest::INIT_SPACE + collection.stable_id.len(),
+        seeds = [b"request", collection.key().as_ref(), queue.tail.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
     #[account(
         mut,
         seeds = [b"mint", placeholder.key().as_ref()],
@@ -90,6 +113,8 @@ pub struct AirdropPlaceholder<'info> {
 impl<'info> AirdropPlaceholder<'info> {
     pub fn airdrop(
         &mut self,
+        expiry: i64,
+        nonce: u64,
         bumps: AirdropPlaceholderBumps,
     ) -> Result<()> {
 
@@ -98,21 +123,35 @@ impl<'info> AirdropPlaceholder<'info> {
             Airdrop Placeholder Nft Ix:
 
             Some security check:
-            - The admin_state.publickey must match the signing admin.
+            - The payer must be the admin wallet, the collection owner, or the buyer self claiming the voucher.
 
             What these Instructions do:
             - Creates a transfer of a placeholder NFT.
             - Invokes a transfer of SOL (price of mint + adminFee) from the buyer to the collection owner & admin.
-            - Increase the total_supply on the collection (total minted nfts).
+            - Increase the total_supply on the collection (total minted nfts), or the airdrop_minted for owner airdrops.
 
             - Airdrop Functionality
-                - Attached to the instructions will be a ED25519 txn w/ a signature and message
-                - If signature matches admin, then the buyer will be airdropped the mint without paying the mint price
-                - The inputted buyer must match the buyer from the ED25519 message
+                - The previous instruction must be a ED25519 signature of the admin or of the collection owner
+                over an Airdrop message (collection, buyer, placeholder, expiry & nonce)
+                - The voucher can be submitted by the admin, or claimed by the buyer who then pays the rent & fees
+                - Vouchers signed by the collection owner come out of the collection airdrop_reserve, the
+                admin ones out of the sale supply
+                - If the signature matches, then the buyer will be airdropped the mint without paying the mint price
+                - The signed message (voucher) can't be used after its expiry, and only once: the
+                Voucher PDA of its nonce is marked as used
+                - Airdrops count against the collection max_per_wallet through the buyer MintRecord
+                - A GenerationRequest is added at the tail of the collection queue for the fulfilment backend
         */
 
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
-        require!(self.payer.key() == constant::admin_wallet::id(), ProtocolError::UnauthorizedAdmin);
+        require!(
+            self.payer.key() == constant::admin_wallet::id()
+                || self.payer.key() == self.collection.owner
+                || self.payer.key() == self.buyer.key(),
+            ProtocolError::UnauthorizedAdmin
+        );
+        require!(Clock::get()?.unix_timestamp <= expiry, ProtocolError::VoucherExpired);
+        require!(!self.voucher.used, ProtocolError::VoucherAlreadyUsed);
 
         let seeds: &[&[u8]; 2] = &[
             b"auth",
@@ -121,125 +160,104 @@ impl<'info> AirdropPlaceholder<'info> {
         let signer_seeds = &[&seeds[..]];
     
 
-        require!(
-            self.collection.max_supply > self.collection.total_supply,
-            BuyingError::SoldOut
-        );
-        
-        // let transfer_instruction_two = system_instruction::transfer(
-        //     &self.collection_owner.key(),
-        //     &self.payer.key(),
-        //     ADMIN_FEE,
-        // );
-
-         // Instruction Check
-         let ixs = self.instructions.to_account_info();
-         let current_index = load_current_index_checked(&ixs)? as usize;
-        
-         // If the current index is greater than 0, then we can check for the airdrop instructions
-         if current_index > 0 {
-             match load_instruction_at_checked(current_index - 1, &ixs) {
-                Ok(signature_ix) => {
-                    if Pubkey::from_str(ED25519_PROGRAM_ID).unwrap() == signature_ix.program_id {
-                         // Ensure signing authority is correct
-                       require!(
-                        constant::admin_wallet::id()
-                             .to_bytes()
-                             .eq(&signature_ix.data[16..48]),
-                         ProtocolError::UnauthorizedAdmin,
-                       );
- 
-                       let mut message_data: [u8; 32] = [0; 32];
-                       message_data.copy_from_slice(&signature_ix.data[112..144]);
-                       let _buyer = Pubkey::from(message_data);
-
-                       require!(
-                         _buyer == *self.buyer.key,
-                         ProtocolError::UnauthorizedAdmin,
-                       );
-
-                    //    invoke(
-                    //     &transfer_instruction_two,
-                    //     &[
-                    //         self.collection_owner.to_account_info(),
-                    //         self.payer.to_account_info(),
-                    //         self.system_program.to_account_info(),
-                    //     ],
-                    //     )?;
-            
-                        // Initialize ATA
-                        create(
-                        CpiContext::new(
-                            self.token_2022_program.to_account_info(),
-                            Create {
-                                payer: self.payer.to_account_info(), // payer
-                                associated_token: self.buyer_mint_ata.to_account_info(),
-                                authority: self.buyer.to_account_info(), // owner
-                                mint: self.mint.to_account_info(),
-                                system_program: self.system_program.to_account_info(),
-                                token_program: self.token_2022_program.to_account_info(),
-                            }
-                        ),
-                        )?;
-
-                        // balance before minting
-                        {
-                            let _before_data = self.buyer_mint_ata.data.borrow();
-                            let _before_state = StateWithExtensions::<TokenAccount>::unpack(&_before_data)?;
-                        
-                            // msg!("before mint balance={}", _before_state.base.amount);
-                        }
-                        
-            
-                        // Mint the mint
-                         mint_to(
-                        CpiContext::new_with_signer(
-                            self.token_2022_program.to_account_info(),
-                            MintTo {
-                                mint: self.mint.to_account_info(),
-                                to: self.buyer_mint_ata.to_account_info(),
-                                authority: self.auth.to_account_info(),
-                            },
-                            signer_seeds
-                        ),
-                        1,
-                        )?;
-                    
-                        self.collection.total_supply += 1;
-            
-                        set_authority(
-                            CpiContext::new_with_signer(
-                                self.token_2022_program.to_account_info(), 
-                                SetAuthority {
-                                    current_authority: self.auth.to_account_info(),
-                                    account_or_mint: self.mint.to_account_info(),
-                                }, 
-                                signer_seeds
-                            ), 
-                            AuthorityType::MintTokens, 
-                        None
-                        )?;
-
-                        // check the post balance of the mint
-                        {
-                            let _after_data = self.buyer_mint_ata.data.borrow();
-                            let _after_state = StateWithExtensions::<TokenAccount>::unpack(&_after_data)?;
-
-                            // msg!("after mint balance={}", _after_state.base.amount);
-
-                            require!(_after_state.base.amount == 1, ProtocolError::InvalidBalancePostMint);
-                        }
-                    } else {
-                        // NO ED25519 instruction
-                        Err(ProtocolError::InstructionsNotCorrect)?;
-                    }
-                }
-                Err(_) => {
-                    // NO ED25519 instruction
-                    Err(ProtocolError::InstructionsNotCorrect)?;
+        // Instruction Check
+        let payload = SignedPayload::Airdrop {
+            collection: self.collection.key(),
+            buyer: self.buyer.key(),
+            placeholder: self.placeholder.key(),
+            expiry,
+            nonce,
+        };
+        let signer = verify_signed_payload_by_any(
+            &self.instructions.to_account_info(),
+            &[constant::admin_wallet::id(), self.collection.owner],
+            &payload
+        )?;
+
+        let owner_airdrop = signer == self.collection.owner;
+        if owner_airdrop {
+            require!(
+                self.collection.airdrop_minted < self.collection.airdrop_reserve,
+                BuyingError::AirdropReserveExhausted
+            );
+        } else {
+            require!(
+                self.collection.total_supply < self.collection.sale_supply(),
+                BuyingError::SoldOut
+            );
+        }
+
+        self.voucher.set_inner(Voucher {
+            nonce,
+            collection: self.collection.key(),
+            buyer: self.buyer.key(),
+            used: true,
+        });
+
+        let collection_key = self.collection.key();
+        self.mint_record.record_mint(&self.collection, collection_key, self.buyer.key())?;
+
+        // Initialize ATA
+        create(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                Create {
+                    payer: self.payer.to_account_info(), // payer
+                    associated_token: self.buyer_mint_ata.to_account_info(),
+                    authority: self.buyer.to_account_info(), // owner
+                    mint: self.mint.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_2022_program.to_account_info(),
                 }
-            }
+            ),
+        )?;
+
+        // Mint the mint
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                MintTo {
+                    mint: self.mint.to_account_info(),
+                    to: self.buyer_mint_ata.to_account_info(),
+                    authority: self.auth.to_account_info(),
+                },
+                signer_seeds
+            ),
+            1,
+        )?;
+
+        if owner_airdrop {
+            self.collection.airdrop_minted += 1;
+        } else {
+            self.collection.total_supply += 1;
         }
+
+        let (collection_key, placeholder_key) = (self.collection.key(), self.placeholder.key());
+        self.queue.enqueue(&mut self.request, &self.collection, collection_key, placeholder_key, self.buyer.key())?;
+
+        set_authority(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(), 
+                SetAuthority {
+                    current_authority: self.auth.to_account_info(),
+                    account_or_mint: self.mint.to_account_info(),
+                }, 
+                signer_seeds
+            ), 
+            AuthorityType::MintTokens, 
+            None
+        )?;
+
+        // check the post balance of the mint
+        {
+            let _after_data = self.buyer_mint_ata.data.borrow();
+            let _after_state = StateWithExtensions::<TokenAccount>::unpack(&_after_data)?;
+
+            // msg!("after mint balance={}", _after_state.base.amount);
+
+            require!(_after_state.base.amount == 1, ProtocolError::InvalidBalancePostMint);
+        }
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,343 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            keccak::hashv,
+            program::{invoke, invoke_signed},
+            program_option::COption,
+            system_instruction,
+            sysvar::instructions,
+        },
+    },
+    anchor_spl::{
+        token_2022::{
+            Token2022,
+            spl_token_2022::{
+                instruction::AuthorityType,
+                state::Mint,
+                extension::StateWithExtensions,
+            }},
+        associated_token::{AssociatedToken, Create, create_idempotent},
+        token_interface::{MintTo, mint_to, set_authority, SetAuthority}
+    },
+};
+use crate::{
+    constant, ed25519::{SignedPayload, verify_signed_payload_by_any}, errors::{BuyingError, ProtocolError},
+    sale::verify_merkle_proof, state::{Collection, GenerationQueue, GenerationRequest, MintRecord, Placeholder, Protocol, Voucher}
+};
+
+// recipient, placeholder, placeholder mint, recipient ATA, mint record & generation request
+const ACCOUNTS_PER_RECIPIENT: usize = 6;
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], expiry: i64, nonce: u64)]
+pub struct BatchAirdrop<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Voucher::INIT_SPACE,
+        seeds = [b"voucher", collection.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub voucher: Box<Account<'info, Voucher>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = GenerationQueue::INIT_SPACE,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    /// CHECK:
+    pub auth: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_2022_program: Program<'info, Token2022>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    pub system_program: Program<'info, System>,
+    #[account(address = instructions::ID)]
+    /// CHECK: InstructionsSysvar account
+    instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> BatchAirdrop<'info> {
+    pub fn batch_airdrop(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        merkle_root: [u8; 32],
+        expiry: i64,
+        nonce: u64,
+        proofs: Vec<Vec<[u8; 32]>>,
+        bumps: BatchAirdropBumps,
+    ) -> Result<u64> {
+
+        /*
+
+            Batch Airdrop Ix:
+
+            Some security check:
+            - The payer must be the admin wallet or the collection owner.
+            - The previous instruction must be a ED25519 signature of the admin or of the collection owner
+            over an AirdropBatch message (collection, merkle_root, expiry & nonce).
+            - The signed message can't be used after its expiry, and only once: the Voucher PDA of its nonce is marked as used.
+            - Every recipient must be in the signed Merkle tree, leaves are keccak(recipient || placeholder).
+
+            What these Instructions do:
+            - Airdrops the placeholders of a campaign passed in as remaining accounts, in groups of
+            recipient, placeholder, placeholder mint, recipient ATA, mint record & generation request.
+            - The supply (sale supply for admin batches, airdrop_reserve for owner batches) is checked for every delivered placeholder.
+            - A campaign can be split over several transactions with the same root, each signed with its own nonce,
+            placeholders that were already minted (or burned since) are skipped: their mint has no mint authority left.
+            - Batch airdrops count against the collection max_per_wallet through the MintRecord of every recipient,
+            created on its first mint. Like Anchor init, the MintRecord & GenerationRequest PDAs can be created
+            even if someone pre-funded their address.
+            - A GenerationRequest is added at the tail of the collection queue for every delivered placeholder.
+            - Returns the number of delivered placeholders through set_return_data.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(
+            self.payer.key() == constant::admin_wallet::id() || self.payer.key() == self.collection.owner,
+            ProtocolError::UnauthorizedAdmin
+        );
+        require!(Clock::get()?.unix_timestamp <= expiry, ProtocolError::VoucherExpired);
+        require!(!self.voucher.used, ProtocolError::VoucherAlreadyUsed);
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.chunks_exact(ACCOUNTS_PER_RECIPIENT).remainder().is_empty(),
+            ProtocolError::InvalidBatch
+        );
+        let batch_size = remaining_accounts.len() / ACCOUNTS_PER_RECIPIENT;
+        require!(proofs.len() == batch_size, ProtocolError::InvalidBatch);
+
+        let collection_key = self.collection.key();
+        let payload = SignedPayload::AirdropBatch {
+            collection: collection_key,
+            merkle_root,
+            expiry,
+            nonce,
+        };
+        let signer = verify_signed_payload_by_any(
+            &self.instructions.to_account_info(),
+            &[constant::admin_wallet::id(), self.collection.owner],
+            &payload
+        )?;
+
+        let owner_airdrop = signer == self.collection.owner;
+
+        // a batch voucher has no single buyer
+        self.voucher.set_inner(Voucher {
+            nonce,
+            collection: collection_key,
+            buyer: Pubkey::default(),
+            used: true,
+        });
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let mut delivered: u64 = 0;
+        for (accounts, proof) in remaining_accounts.chunks(ACCOUNTS_PER_RECIPIENT).zip(proofs.iter()) {
+            let [recipient, placeholder_info, mint, recipient_ata, mint_record, request] = accounts else {
+                return Err(ProtocolError::InvalidBatch.into());
+            };
+
+            let placeholder: Account<Placeholder> = Account::try_from(placeholder_info)?;
+            require!(placeholder.collection == collection_key, BuyingError::CollectionMismatch);
+
+            let leaf = hashv(&[recipient.key.as_ref(), placeholder_info.key.as_ref()]).0;
+            require!(verify_merkle_proof(proof, &merkle_root, leaf), ProtocolError::InvalidBatch);
+
+            let (mint_key, _) = Pubkey::find_program_address(&[b"mint", placeholder_info.key.as_ref()], &crate::ID);
+            require!(mint_key == mint.key(), ProtocolError::InvalidBatch);
+
+            // already delivered by a previous batch of the campaign or burned since, a single one must not revert the batch
+            let deliverable = mint.owner == &spl_token_2022::ID && {
+                let mint_data = mint.try_borrow_data()?;
+                StateWithExtensions::<Mint>::unpack(&mint_data)
+                    .is_ok_and(|state| state.base.mint_authority == COption::Some(self.auth.key()))
+            };
+            if !deliverable {
+                continue;
+            }
+
+            if owner_airdrop {
+                require!(
+                    self.collection.airdrop_minted + delivered < self.collection.airdrop_reserve,
+                    BuyingError::AirdropReserveExhausted
+                );
+            } else {
+                require!(
+                    self.collection.total_supply + delivered < self.collection.sale_supply(),
+                    BuyingError::SoldOut
+                );
+            }
+
+            self.record_mint(mint_record, recipient.key())?;
+
+            create_idempotent(
+                CpiContext::new(
+                    self.associated_token_program.to_account_info(),
+                    Create {
+                        payer: self.payer.to_account_info(),
+                        associated_token: recipient_ata.to_account_info(),
+                        authority: recipient.to_account_info(),
+                        mint: mint.to_account_info(),
+                        system_program: self.system_program.to_account_info(),
+                        token_program: self.token_2022_program.to_account_info(),
+                    }
+                ),
+            )?;
+
+            mint_to(
+                CpiContext::new_with_signer(
+                    self.token_2022_program.to_account_info(),
+                    MintTo {
+                        mint: mint.to_account_info(),
+                        to: recipient_ata.to_account_info(),
+                        authority: self.auth.to_account_info(),
+                    },
+                    signer_seeds
+                ),
+                1,
+            )?;
+
+            set_authority(
+                CpiContext::new_with_signer(
+                    self.token_2022_program.to_account_info(),
+                    SetAuthority {
+                        current_authority: self.auth.to_account_info(),
+                        account_or_mint: mint.to_account_info(),
+                    },
+                    signer_seeds
+                ),
+                AuthorityType::MintTokens,
+                None
+            )?;
+
+            self.create_request(request, recipient.key(), placeholder_info.key())?;
+
+            delivered += 1;
+        }
+
+        if owner_airdrop {
+            self.collection.airdrop_minted += delivered;
+        } else {
+            self.collection.total_supply += delivered;
+        }
+
+        Ok(delivered)
+    }
+
+    // Counts the airdrop on the MintRecord PDA of the recipient, created on its first mint like the GenerationRequest
+    fn record_mint(&mut self, mint_record: &'info AccountInfo<'info>, recipient: Pubkey) -> Result<()> {
+        let collection_key = self.collection.key();
+        let (mint_record_key, mint_record_bump) = Pubkey::find_program_address(
+            &[b"mint_record", collection_key.as_ref(), recipient.as_ref()],
+            &crate::ID
+        );
+        require!(mint_record_key == mint_record.key(), ProtocolError::InvalidBatch);
+
+        let mut record = if mint_record.data_is_empty() {
+            let seeds: &[&[u8]; 4] = &[
+                b"mint_record",
+                collection_key.as_ref(),
+                recipient.as_ref(),
+                &[mint_record_bump],
+            ];
+
+            create_pda(&self.payer.to_account_info(), mint_record, MintRecord::INIT_SPACE, &seeds[..])?;
+
+            MintRecord {
+                collection: Pubkey::default(),
+                buyer: Pubkey::default(),
+                minted: 0,
+                phase_minted: [0; constant::MAX_SALE_PHASES],
+            }
+        } else {
+            require!(mint_record.owner == &crate::ID, ProtocolError::InvalidBatch);
+            MintRecord::try_deserialize(&mut &mint_record.try_borrow_data()?[..])?
+        };
+
+        record.record_mint(&self.collection, collection_key, recipient)?;
+        record.try_serialize(&mut &mut mint_record.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+
+    // Creates the GenerationRequest PDA at the queue tail, it can't go through the Accounts constraints as it's a remaining account
+    fn create_request(&mut self, request: &'info AccountInfo<'info>, buyer: Pubkey, placeholder: Pubkey) -> Result<()> {
+        let collection_key = self.collection.key();
+        let seq = self.queue.tail.to_le_bytes();
+        let (request_key, request_bump) = Pubkey::find_program_address(
+            &[b"request", collection_key.as_ref(), seq.as_ref()],
+            &crate::ID
+        );
+        require!(request_key == request.key(), ProtocolError::InvalidBatch);
+
+        let space = GenerationRequest::INIT_SPACE + self.collection.stable_id.len();
+        let seeds: &[&[u8]; 4] = &[
+            b"request",
+            collection_key.as_ref(),
+            seq.as_ref(),
+            &[request_bump],
+        ];
+
+        create_pda(&self.payer.to_account_info(), request, space, &seeds[..])?;
+
+        let mut generation_request = GenerationRequest::default();
+        self.queue.enqueue(&mut generation_request, &self.collection, collection_key, placeholder, buyer)?;
+        generation_request.try_serialize(&mut &mut request.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
+
+/*
+    Creates a program owned PDA the way Anchor init does: create_account when the address is empty, otherwise
+    (someone pre-funded it) tops up the rent, then allocates & assigns it, so a transfer can't block the batch.
+*/
+fn create_pda<'info>(payer: &AccountInfo<'info>, account: &AccountInfo<'info>, space: usize, signer_seeds: &[&[u8]]) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(payer.key, account.key, rent, space as u64, &crate::ID),
+            &[payer.clone(), account.clone()],
+            &[signer_seeds]
+        ).map_err(Into::into);
+    }
+
+    let missing_lamports = rent.saturating_sub(account.lamports());
+    if missing_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, missing_lamports),
+            &[payer.clone(), account.clone()],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        std::slice::from_ref(account),
+        &[signer_seeds]
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(account.key, &crate::ID),
+        std::slice::from_ref(account),
+        &[signer_seeds]
+    ).map_err(Into::into)
+}
@@ -0,0 +1,120 @@
+use {
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_2022::Token2022,
+        associated_token::AssociatedToken,
+        token_interface::{CloseAccount, close_account},
+    },
+};
+use crate::state::{Protocol, AiNft, Collection, TraitStats};
+use crate::errors::ProtocolError;
+use crate::delivery::{BurnAccounts, burn_ai_nft};
+
+#[derive(Accounts)]
+pub struct BurnNft<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            holder.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub holder_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = nft.collection,
+    )]
+    pub collection: Account<'info, Collection>,
+    #[account(
+        mut,
+        seeds = [b"ainft", nft.collection.key().as_ref(), nft.id.to_le_bytes().as_ref()],
+        bump,
+        close = holder,
+    )]
+    pub nft: Account<'info, AiNft>,
+    #[account(
+        mut,
+        seeds = [b"trait_stats", collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Box<Account<'info, TraitStats>>,
+    /// CHECK: the AiNft mint
+    #[account(
+        mut,
+        address = nft.mint,
+    )]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_2022_program: Program<'info, Token2022>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+}
+
+impl<'info> BurnNft<'info> {
+    pub fn burn_nft(
+        &mut self,
+        bumps: BurnNftBumps,
+    ) -> Result<()> {
+
+        /*
+
+            Burn Nft Ix:
+
+            Some security check:
+            - The holder of the AiNft must sign, the burn fails if their ATA doesn't hold the token.
+
+            What these Instructions do:
+            - Burns the AiNft token and closes the holder ATA.
+            - Closes the AiNft mint through its MintCloseAuthority (the auth PDA) and the AiNft account,
+            all the rent goes back to the holder. This is intended: the AiNft doesn't record who funded it
+            (admin or buyer), and the rent is the only thing the holder gets back for destroying the token.
+            - Decreases the collection mint_count and uncounts the AiNft traits from the rarity counters.
+            - The collection mint only has a group pointer (no TokenGroup is initialized), so there is
+            no group size to update.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+
+        burn_ai_nft(
+            &BurnAccounts {
+                holder: self.holder.to_account_info(),
+                holder_mint_ata: self.holder_mint_ata.to_account_info(),
+                mint: self.mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+            },
+            bumps.auth,
+        )?;
+
+        close_account(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                CloseAccount {
+                    account: self.holder_mint_ata.to_account_info(),
+                    destination: self.holder.to_account_info(),
+                    authority: self.holder.to_account_info(),
+                },
+            ),
+        )?;
+
+        self.collection.mint_count = self.collection.mint_count.checked_sub(1).ok_or(ProtocolError::CounterUnderflow)?;
+        self.trait_stats.erase(&self.nft.attributes)?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,3 @@
-use solana_program::native_token::LAMPORTS_PER_SOL;
 use {
     anchor_lang::prelude::*,
     anchor_spl::{
@@ -21,8 +20,14 @@ use crate::{
         ADMIN_FEE, 
         // ADMIN_PERCENTAGE
     }, 
-    errors::{BuyingError, ProtocolError}, state::{Collection, Placeholder, Protocol}
+    errors::{BuyingError, ProtocolError}, 
+    state::{Collection, Escrow, GenerationQueue, GenerationRequest, MintRecord, PhaseGate, Placeholder, Protocol},
+    sale::verify_allowlist_proof,
+    escrow::escrow_deadline,
+    metadata::update_metadata_field,
+    moderation::check_text,
 };
+use spl_token_metadata_interface::state::Field;
 
 #[derive(Accounts)]
 pub struct BuyPlaceholder<'info> {
@@ -51,8 +56,41 @@ pub struct BuyPlaceholder<'info> {
         mut,
         seeds = [b"placeholder", placeholder.collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
         bump,
+        constraint = placeholder.collection == collection.key() @ BuyingError::CollectionMismatch,
     )] 
     pub placeholder: Account<'info, Placeholder>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MintRecord::INIT_SPACE,
+        seeds = [b"mint_record", collection.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub mint_record: Account<'info, MintRecord>,
+    #[account(
+        init,
+        payer = payer,
+        space = Escrow::INIT_SPACE,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = GenerationQueue::INIT_SPACE,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        init,
+        payer = payer,
+        space = GenerationRequest::INIT_SPACE + collection.stable_id.len(),
+        seeds = [b"request", collection.key().as_ref(), queue.tail.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
     #[account(
         mut,
         seeds = [b"mint", placeholder.key().as_ref()],
@@ -74,12 +112,26 @@ pub struct BuyPlaceholder<'info> {
         bump,
     )]
     pub protocol: Account<'info, Protocol>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_moderation: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> BuyPlaceholder<'info> {
     pub fn buy(
         &mut self,
+        allowlist_proof: Vec<[u8; 32]>,
+        prompt: Option<String>,
         bumps: BuyPlaceholderBumps,
     ) -> Result<()> {
 
@@ -92,8 +144,31 @@ impl<'info> BuyPlaceholder<'info> {
 
             What these Instructions do:
             - Creates a transfer of a placeholder NFT.
-            - Invokes a transfer of SOL (price of mint + adminFee) from the buyer to the collection owner & admin.
+            - Invokes a transfer of SOL (price of mint + adminFee) from the buyer to the placeholder escrow, released
+            to the collection owner & admin in transfer_nft or refunded after the collection delivery deadline.
             - Increase the total_supply on the collection (total minted nfts).
+
+            - Sale Phases
+                - If the collection has phases, the phase live at the current time sets the price
+                - The phase supply cap, per wallet limit and gating rule (allowlist proof) are enforced
+                - The MintRecord of the buyer keeps track of the mints per phase
+
+            - Wallet Limit
+                - The MintRecord of the buyer is created on first use and incremented on every mint
+                - A wallet can't mint more than the collection max_per_wallet
+
+            - Pricing
+                - Fixed: the active phase price, or the collection price if the collection has no phases
+                - Dutch Auction: the price decays from the sale start time down to the floor price
+                - Bonding Curve: the price grows with the collection total_supply
+                - The price actually paid (lamports) is recorded on the placeholder and in its mint metadata
+
+            - Generation Queue
+                - A GenerationRequest is added at the tail of the collection queue for the fulfilment backend
+
+            - Prompt
+                - If the collection accepts prompts, the buyer prompt is stored on the Placeholder and
+                in the placeholder mint metadata for the generation backend
         */
 
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
@@ -114,10 +189,43 @@ impl<'info> BuyPlaceholder<'info> {
         );
 
         require!(
-            self.collection.total_supply < self.collection.max_supply,
+            self.collection.total_supply < self.collection.sale_supply(),
             BuyingError::SoldOut
         );
 
+        let collection_key = self.collection.key();
+        self.mint_record.record_mint(&self.collection, collection_key, self.buyer.key())?;
+
+        let active_phase = self.collection.active_phase(current_time)?;
+        let fixed_price = match active_phase {
+            Some(index) => {
+                let phase = self.collection.phases[index].clone();
+
+                if let PhaseGate::Allowlist { merkle_root } = phase.gate {
+                    require!(
+                        verify_allowlist_proof(&allowlist_proof, &merkle_root, &self.buyer.key()),
+                        BuyingError::NotInWhitelist
+                    );
+                }
+
+                require!(
+                    phase.supply_cap == 0 || phase.minted < phase.supply_cap,
+                    BuyingError::PhaseSoldOut
+                );
+
+                require!(
+                    phase.max_per_wallet == 0 || self.mint_record.phase_minted[index] < phase.max_per_wallet,
+                    BuyingError::PhaseWalletLimitReached
+                );
+
+                self.collection.phases[index].minted += 1;
+                self.mint_record.phase_minted[index] += 1;
+
+                phase.price
+            }
+            None => self.collection.price,
+        };
+
         let seeds: &[&[u8]; 2] = &[
             b"auth",
             &[bumps.auth],
@@ -125,40 +233,39 @@ impl<'info> BuyPlaceholder<'info> {
         let signer_seeds = &[&seeds[..]];
         
 
-        // Pay the mint
-        let amount_in_lamports = ((self.placeholder.price * LAMPORTS_PER_SOL as f32) as u64) - ADMIN_FEE;  //// ex. should be (0.3 * 1000000000) - 100000000 = 200000000
-        let transfer_instruction = system_instruction::transfer(
-            &self.buyer.key(),
-            &self.collection_owner.key(),
-            amount_in_lamports as u64,
-        );  
+        let price_in_lamports = self.collection.current_price(current_time, fixed_price)?;
+        self.placeholder.paid_price = price_in_lamports;
 
-        // let _admin_fee = ((self.placeholder.price * ADMIN_PERCENTAGE) * LAMPORTS_PER_SOL as f32) + ADMIN_FEE as f32;
+        require!(price_in_lamports >= ADMIN_FEE, ProtocolError::InvalidPrice);
 
-        let transfer_instruction_two = system_instruction::transfer(
+        // Pay the mint into the escrow, the collection owner & admin get paid once the AiNft is delivered
+        let transfer_instruction = system_instruction::transfer(
             &self.buyer.key(),
-            &self.payer.key(),
-            ADMIN_FEE as u64,
+            &self.escrow.key(),
+            price_in_lamports,
         );
 
-        
+        // let _admin_fee = ((self.collection.price * ADMIN_PERCENTAGE) * LAMPORTS_PER_SOL as f32) + ADMIN_FEE as f32;
+
         invoke(
             &transfer_instruction,
             &[
                 self.buyer.to_account_info(),
-                self.collection_owner.to_account_info(),
+                self.escrow.to_account_info(),
                 self.system_program.to_account_info(),
             ],
         )?;
 
-        invoke(
-            &transfer_instruction_two,
-            &[
-                self.buyer.to_account_info(),
-                self.payer.to_account_info(),
-                self.system_program.to_account_info(),
-            ],
-        )?;
+        self.escrow.set_inner(Escrow {
+            placeholder: self.placeholder.key(),
+            buyer: self.buyer.key(),
+            fee_recipient: self.payer.key(),
+            amount: price_in_lamports,
+            fee: ADMIN_FEE,
+            deadline: escrow_deadline(current_time, self.collection.delivery_window)?,
+            phase: active_phase.map(|index| index as u8),
+            reroll: false,
+        });
 
         // Initialize ATA
         create(
@@ -200,6 +307,39 @@ impl<'info> BuyPlaceholder<'info> {
 
         self.collection.total_supply += 1;
 
+        update_metadata_field(
+            &self.token_2022_program.to_account_info(),
+            &self.mint.to_account_info(),
+            &self.auth.to_account_info(),
+            &self.payer.to_account_info(),
+            &self.system_program.to_account_info(),
+            Field::Key("price".to_string()),
+            price_in_lamports.to_string(),
+            signer_seeds,
+        )?;
+
+        let (collection_key, placeholder_key) = (self.collection.key(), self.placeholder.key());
+        self.queue.enqueue(&mut self.request, &self.collection, collection_key, placeholder_key, self.buyer.key())?;
+
+        if let Some(prompt) = prompt.filter(|prompt| !prompt.is_empty()) {
+            require!(self.collection.prompt_enabled, BuyingError::PromptDisabled);
+            require!(prompt.len() <= self.collection.max_prompt_len as usize, BuyingError::PromptTooLong);
+            check_text(&[&self.protocol_moderation, &self.collection_moderation], &[&prompt])?;
+
+            update_metadata_field(
+                &self.token_2022_program.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.auth.to_account_info(),
+                &self.payer.to_account_info(),
+                &self.system_program.to_account_info(),
+                Field::Key("prompt".to_string()),
+                prompt.clone(),
+                signer_seeds,
+            )?;
+
+            self.placeholder.prompt = prompt;
+        }
+
         // msg!("Total supply: {}", self.collection.total_supply);
 
         set_authority(
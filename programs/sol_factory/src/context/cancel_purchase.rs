@@ -0,0 +1,186 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::program::invoke_signed
+    },
+    anchor_spl::{
+        token_2022::{
+            Token2022,
+            spl_token_2022::{
+                state::Account as TokenAccount,
+                extension::StateWithExtensions,
+            }},
+        associated_token::AssociatedToken,
+    },
+};
+use spl_token_2022::instruction::burn;
+use crate::{
+    errors::{BuyingError, ProtocolError},
+    escrow::cancel_escrow,
+    state::{Collection, Escrow, GenerationQueue, GenerationRequest, GenerationStatus, MintRecord, Placeholder, Protocol},
+};
+
+#[derive(Accounts)]
+pub struct CancelPurchase<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"collection", collection.owner.key().as_ref()],
+        bump,
+    )] 
+    pub collection: Account<'info, Collection>,
+    #[account(
+        seeds = [b"placeholder", placeholder.collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = placeholder.collection == collection.key() @ BuyingError::CollectionMismatch,
+    )] 
+    pub placeholder: Account<'info, Placeholder>,
+    #[account(
+        mut,
+        seeds = [b"mint", placeholder.key().as_ref()],
+        bump
+    )]
+    /// CHECK
+    pub placeholder_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            holder.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            placeholder_mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub holder_placeholder_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        mut,
+        seeds = [b"request", collection.key().as_ref(), request.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = request.placeholder == placeholder.key() @ ProtocolError::RequestMismatch,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
+    /// CHECK: checked against the escrow fee_recipient
+    #[account(mut)]
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"mint_record", collection.key().as_ref(), escrow.buyer.as_ref()],
+        bump,
+    )]
+    pub mint_record: Option<Account<'info, MintRecord>>,
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    /// CHECK:
+    pub auth: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_2022_program: Program<'info, Token2022>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelPurchase<'info> {
+    pub fn cancel(
+        &mut self,
+        bumps: CancelPurchaseBumps,
+    ) -> Result<()> {
+
+        /*
+        
+            Cancel Purchase Ix:
+
+            Some security check:
+            - The generation of the Placeholder must still be Queued.
+            - The signer must hold the placeholder.
+
+            What these Instructions do:
+            - Burns the Placeholder NFT through the permanent delegate (auth).
+            - Refunds the price paid minus the collection cancellation fee from the escrow.
+            - Decrease the total_supply on the collection, the minted count of the sale phase and the MintRecord of the buyer
+            (required), unless the escrow holds a reroll fee.
+            - Flags the GenerationRequest as cancelled and counts it as completed, it is skipped when claimed.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(
+            self.placeholder.status == GenerationStatus::Queued,
+            BuyingError::GenerationAlreadyStarted
+        );
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+
+        {
+            let _before_burn_data = self.holder_placeholder_mint_ata.data.borrow();
+            let _before_burn_state = StateWithExtensions::<TokenAccount>::unpack(&_before_burn_data)?;
+
+            require!(_before_burn_state.base.amount > 0, ProtocolError::InvalidBalancePreBurn);
+        }
+
+        // Burn the placeholder nft
+        let ix = burn(
+            self.token_2022_program.key,
+            self.holder_placeholder_mint_ata.key,
+            self.placeholder_mint.key,
+            self.auth.key,
+            &[],
+            1,
+        )?;
+
+        invoke_signed(
+            &ix,
+            &[
+                self.holder_placeholder_mint_ata.to_account_info(),
+                self.placeholder_mint.to_account_info(),
+                self.auth.to_account_info(),
+                self.token_2022_program.to_account_info(),
+            ],
+            &[&seeds[..]],
+        )?;
+
+        {
+            let _after_burn_data = self.holder_placeholder_mint_ata.data.borrow();
+            let _after_burn_state = StateWithExtensions::<TokenAccount>::unpack(&_after_burn_data)?;
+
+            require!(_after_burn_state.base.amount == 0, ProtocolError::InvalidBalancePostBurn);
+        }
+
+        cancel_escrow(
+            &self.escrow,
+            &self.holder.to_account_info(),
+            &self.fee_recipient,
+            self.collection.cancellation_fee_bps,
+        )?;
+
+        // a reroll fee was paid for a serial that is already counted, and stays so
+        if !self.escrow.reroll {
+            self.collection.unrecord_sale(self.escrow.phase)?;
+            self.mint_record.as_mut().ok_or(ProtocolError::MintRecordRequired)?.unrecord_mint(self.escrow.phase)?;
+        }
+        self.queue.cancel(&mut self.request);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::{
+    state::{Admin, GenerationQueue, GenerationRequest, GenerationStatus, Placeholder, Protocol},
+    errors::ProtocolError,
+};
+
+#[derive(Accounts)]
+pub struct ClaimGenerationRequest<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        mut,
+        seeds = [b"queue", request.collection.as_ref()],
+        bump,
+    )]
+    pub queue: Account<'info, GenerationQueue>,
+    #[account(
+        mut,
+        seeds = [b"request", request.collection.as_ref(), request.seq.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Account<'info, GenerationRequest>,
+    #[account(
+        mut,
+        address = request.placeholder,
+    )]
+    pub placeholder: Account<'info, Placeholder>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+}
+
+impl<'info> ClaimGenerationRequest<'info> {
+    pub fn claim(
+        &mut self,
+    ) -> Result<()> {
+
+        /*
+        
+            Claim Generation Request Ix:
+
+            Some security check:
+            - The admin_state.publickey must match the signing admin (fulfilment admin).
+            - Requests are claimed in FIFO order, the request must be at the head of the queue.
+
+            What these Instructions do:
+            - Claims the request for the admin and moves the Placeholder generation status to Generating.
+            - Cancelled requests are closed instead, they were already counted as completed when cancelled.
+            - Moves the head of the queue to the next request.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(self.request.seq == self.queue.head, ProtocolError::RequestOutOfOrder);
+
+        self.queue.head += 1;
+
+        if self.request.cancelled {
+            self.request.close(self.admin.to_account_info())?;
+
+            return Ok(());
+        }
+
+        self.request.claimed_by = Some(self.admin.key());
+
+        if self.placeholder.status == GenerationStatus::Queued {
+            self.placeholder.status = GenerationStatus::Generating;
+        }
+
+        Ok(())
+    }
+}
@@ -43,6 +43,7 @@ impl<'info> CloseCollection<'info> {
 
             What these Instructions do:
             - Closes the collection by updating the sale end time to the current moment and setting the max supply to the total supply.
+            - The unused part of the airdrop reserve is released as well.
         */
 
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
@@ -51,7 +52,8 @@ impl<'info> CloseCollection<'info> {
 
         // update the collection to sale_end_time: Clock::get()?.unix_timestamp, and max_supply: self.collection.total_supply,
         self.collection.sale_end_time = Clock::get()?.unix_timestamp;
-        self.collection.max_supply = self.collection.total_supply;
+        self.collection.max_supply = self.collection.total_supply + self.collection.airdrop_minted;
+        self.collection.airdrop_reserve = self.collection.airdrop_minted;
                 
 
         Ok(())
@@ -6,12 +6,17 @@ use anchor_lang::{
     prelude::*
 };
 pub use anchor_spl::token_2022::Token2022;
-use crate::state::{Collection, Protocol, Admin};
+use crate::state::{Collection, CollectionConfig, Protocol, Admin, SalePhase};
+use crate::constant::{BPS_DENOMINATOR, MAX_DELIVERY_WINDOW};
 use crate::errors::ProtocolError;
+use crate::sale::validate_phases;
+use crate::moderation::check_text;
 pub use spl_token_2022::{
     extension::ExtensionType,
     extension::group_pointer::instruction::initialize as initialize_group_pointer,
+    instruction::initialize_mint2,
 };
+use spl_token_group_interface::instruction::initialize_group;
 
 #[derive(Accounts)]
 #[instruction(
@@ -24,6 +29,7 @@ pub use spl_token_2022::{
     max_supply: u64,
     price: f32,
     stable_id: String,
+    config: CollectionConfig,
 )]
 pub struct CreateCollection<'info> {
     #[account(mut)]
@@ -36,7 +42,7 @@ pub struct CreateCollection<'info> {
         seeds = [b"collection", owner.key().as_ref()],
         bump,
         payer = admin,
-        space = Collection::INIT_SPACE + 54 + url.len() + name.len() + stable_id.len(),
+        space = Collection::INIT_SPACE + 54 + url.len() + name.len() + stable_id.len() + config.phases.iter().map(|phase| SalePhase::INIT_SPACE + phase.name.len()).sum::<usize>(),
     )] 
     pub collection: Account<'info, Collection>,
     #[account(
@@ -51,6 +57,12 @@ pub struct CreateCollection<'info> {
         bump
     )]
     pub mint: UncheckedAccount<'info>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
     #[account(address = RENT_ID)]
     /// CHECK: this is fine since we are hard coding the rent sysvar.
     pub rent: UncheckedAccount<'info>,
@@ -60,6 +72,12 @@ pub struct CreateCollection<'info> {
         bump,
     )]
     pub protocol: Account<'info, Protocol>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -75,6 +93,7 @@ impl<'info> CreateCollection<'info> {
         max_supply: u64,
         price: f32,
         stable_id: String,
+        config: CollectionConfig,
         bumps: CreateCollectionBumps,
     ) -> Result<()> {
 
@@ -87,11 +106,44 @@ impl<'info> CreateCollection<'info> {
 
             What these Instructions do:
             - Creates a Collection that can be used to mint NFTs.
+            - Optionally splits the sale into phases (ex. OG, allowlist, public) with their own
+            time window, price, supply cap, per wallet limit and gating rule.
+            - Optionally limits the number of placeholders a single wallet can mint (max_per_wallet).
+            - Sets the pricing mode: Fixed (collection/phase price), a Dutch Auction or a linear/exponential
+            bonding curve computed on-chain.
+            - Sets the delivery window (up to MAX_DELIVERY_WINDOW), after which buyers can refund a placeholder that wasn't delivered.
+            - Sets the fee (in bps of the price paid) kept when a buyer cancels before the generation starts.
+            - Optionally lets buyers pass a prompt (up to max_prompt_len bytes) to the generation.
+            - Registers the generator attestor, the backend key that has to sign every AiNft minted in the collection.
+            - Reserves airdrop_reserve of the max supply for the owner airdrops, the public sale (phases and
+            bonding curve included) only gets the rest.
+            - Sets the reveal mode: Burn (a new AiNft mint replaces the placeholder) or InPlace (the
+            placeholder mint is rewritten into the AiNft).
+            - Sets the reroll fee holders pay to burn their AiNft for a new generation, 0 disables rerolls.
+            - Optionally makes the collection soulbound, its placeholder & AiNft mints are NonTransferable
+            so the tokens stay with the buyer (they can still be burned).
+            - Creates the collection mint as the TokenGroup of the collection, the auth PDA is its mint & update
+            authority so InPlace placeholder mints can be added as TokenGroupMembers.
         */
 
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
         require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
 
+        let CollectionConfig {
+            phases,
+            max_per_wallet,
+            pricing_mode,
+            delivery_window,
+            cancellation_fee_bps,
+            prompt_enabled,
+            max_prompt_len,
+            attestor,
+            airdrop_reserve,
+            reveal_mode,
+            reroll_fee,
+            soulbound,
+        } = config;
+
         // sanity check
 
         require!(sale_start_time < sale_end_time, ProtocolError::InvalidSaleTime);
@@ -99,6 +151,14 @@ impl<'info> CreateCollection<'info> {
         require!(sale_end_time > 0, ProtocolError::InvalidSaleTime);
         require!(max_supply > 0, ProtocolError::InvalidMaxSupply);
         require!(price >= 0.0, ProtocolError::InvalidPrice);
+        check_text(&[&self.protocol_moderation], &[&name, &symbol, &url, &stable_id])?;
+        require!(airdrop_reserve <= max_supply, ProtocolError::InvalidAirdropReserve);
+        let sale_supply = max_supply - airdrop_reserve;
+        validate_phases(&phases, sale_start_time, sale_end_time, sale_supply)?;
+        pricing_mode.validate(sale_supply)?;
+        require!(delivery_window > 0 && delivery_window <= MAX_DELIVERY_WINDOW, ProtocolError::InvalidDeliveryWindow);
+        require!(u64::from(cancellation_fee_bps) <= BPS_DENOMINATOR, ProtocolError::InvalidCancellationFee);
+        require!(attestor != Pubkey::default(), ProtocolError::InvalidAttestor);
 
         
         // msg!("Sale start time is {}", sale_start_time);
@@ -120,18 +180,37 @@ impl<'info> CreateCollection<'info> {
                 mint_count: 0,
                 price,
                 stable_id,
+                phases: phases.into_iter().map(|phase| SalePhase { minted: 0, ..phase }).collect(),
+                max_per_wallet,
+                pricing_mode,
+                delivery_window,
+                cancellation_fee_bps,
+                prompt_enabled,
+                max_prompt_len: if prompt_enabled { max_prompt_len } else { 0 },
+                attestor,
+                airdrop_reserve,
+                airdrop_minted: 0,
+                reveal_mode,
+                reroll_fee,
+                soulbound,
             }
         );
 
-        // Step 1: Initialize Account
+        // Step 1: Initialize Account, funded for the TokenGroup added once the mint is initialized
         let size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
             &[
                 ExtensionType::GroupPointer
             ],
         ).unwrap();
+        let group_size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+            &[
+                ExtensionType::GroupPointer,
+                ExtensionType::TokenGroup,
+            ],
+        ).unwrap();
 
         let rent = &Rent::from_account_info(&self.rent.to_account_info())?;
-        let lamports = rent.minimum_balance(size );
+        let lamports = rent.minimum_balance(group_size);
 
         let collection_key = self.collection.key();
         let seeds: &[&[u8]; 3] = &[
@@ -168,6 +247,42 @@ impl<'info> CreateCollection<'info> {
             ],  
         )?;
 
+        // Step 2: Initialize Mint & TokenGroup, the group has no max size as rerolls add members too
+        invoke(
+            &initialize_mint2(
+                &self.token_2022_program.key(),
+                &self.mint.key(),
+                &self.auth.key(),
+                None,
+                0,
+            )?,
+            &[
+                self.mint.to_account_info(),
+            ],
+        )?;
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        invoke_signed(
+            &initialize_group(
+                &self.token_2022_program.key(),
+                &self.mint.key(),
+                &self.mint.key(),
+                &self.auth.key(),
+                Some(self.auth.key()),
+                u32::MAX,
+            ),
+            &[
+                self.mint.to_account_info(),
+                self.auth.to_account_info(),
+            ],
+            signer_seeds
+        )?;
+
         Ok(())
     }
 
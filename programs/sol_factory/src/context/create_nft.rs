@@ -1,26 +1,15 @@
 pub use anchor_lang::{
-    solana_program::{
-        sysvar::rent::ID as RENT_ID,
-        program::{invoke, invoke_signed}
-    },
+    solana_program::sysvar::{rent::ID as RENT_ID, instructions},
     prelude::*
 };
 pub use anchor_spl::token_2022::Token2022;
-pub use spl_token_2022::{
-    extension::ExtensionType,
-    instruction::initialize_mint2,
-    extension::metadata_pointer::instruction::initialize as initialize_metadata_pointer,
-    extension::group_member_pointer::instruction::initialize as initialize_group_member_pointer,
-};
-pub use spl_token_metadata_interface::{
-    state::{TokenMetadata, Field},
-    instruction::{initialize as initialize_metadata_account, update_field as update_metadata_account},
-};
-pub use crate::state::{Protocol, Collection, Admin, AiNft, Attributes};
+pub use crate::state::{Protocol, Collection, Admin, AiNft, Attributes, Provenance};
+use crate::state::{Placeholder, RevealMode, TraitStats};
 pub use crate::errors::ProtocolError;
+use crate::delivery::{AiNftMintAccounts, ai_nft_metadata, create_ai_nft_mint};
 
 #[derive(Accounts)]
-#[instruction(id: u64, uri: String, name: String, attributes: Vec<Attributes>)]
+#[instruction(id: u64, uri: String, name: String, attributes: Vec<Attributes>, provenance: Provenance)]
 pub struct CreateNft<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -34,6 +23,11 @@ pub struct CreateNft<'info> {
         bump,
     )] 
     pub collection: Account<'info, Collection>,
+    #[account(
+        seeds = [b"placeholder", collection.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub placeholder: Account<'info, Placeholder>,
     /// CHECK: this is fine since we are handling all the checks and creation in the program.
     #[account(
         mut,
@@ -46,7 +40,7 @@ pub struct CreateNft<'info> {
         payer = admin,
         seeds = [b"ainft", collection.key().as_ref(), id.to_le_bytes().as_ref()],
         bump,
-        space = AiNft::INIT_SPACE + attributes.iter().map(|attr| attr.key.len() + attr.value.len()).sum::<usize>(),
+        space = AiNft::space(&provenance.model, &attributes),
     )] 
     pub nft: Account<'info, AiNft>,
     /// CHECK: this is fine since we are handling all the checks and creation in the program.
@@ -71,7 +65,36 @@ pub struct CreateNft<'info> {
         bump,
     )]
     pub protocol: Account<'info, Protocol>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection trait schema, may not be initialized
+    #[account(
+        seeds = [b"trait_schema", collection.key().as_ref()],
+        bump
+    )]
+    pub trait_schema: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TraitStats::INIT_SPACE,
+        seeds = [b"trait_stats", collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Box<Account<'info, TraitStats>>,
     pub system_program: Program<'info, System>,
+    #[account(address = instructions::ID)]
+    /// CHECK: InstructionsSysvar account
+    pub instructions: UncheckedAccount<'info>,
 }
 
 impl<'info> CreateNft<'info> {
@@ -81,6 +104,7 @@ impl<'info> CreateNft<'info> {
         uri: String,
         name: String,
         attributes: Vec<Attributes>,
+        provenance: Provenance,
         bumps: CreateNftBumps,
     ) -> Result<()> {
 
@@ -90,153 +114,70 @@ impl<'info> CreateNft<'info> {
 
             Some security check:
             - The admin_state.publickey must match the signing admin.
+            - The previous instruction must be an Ed25519 signature of the collection attestor over a Generation
+            message (collection, placeholder, sha256(uri), sha256(name), sha256(attributes), sha256(provenance)), so an admin
+            key alone can't mint arbitrary art.
 
             What these Instructions do:
             - Creates a AI NFT from the passed in uri, name, and attributes.
+            - Stores the generation provenance (model, seed, prompt hash & image hash) on the AiNft and
+            under standardized metadata keys, so anyone can check an image against what was minted.
         */
 
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
         require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(self.collection.reveal_mode == RevealMode::Burn, ProtocolError::WrongRevealMode);
+
+        let metadata = ai_nft_metadata(
+            &self.collection,
+            self.placeholder.key(),
+            &self.instructions.to_account_info(),
+            &[&self.protocol_moderation, &self.collection_moderation],
+            &self.trait_schema,
+            self.mint.key(),
+            self.auth.key(),
+            uri,
+            name,
+            attributes.clone(),
+            provenance.clone(),
+        )?;
+
+        let collection_key = self.collection.key();
+        self.trait_stats.record(collection_key, &attributes)?;
 
         self.nft.set_inner(
             AiNft {
                 id,
                 collection: self.collection.key(),
                 reference: self.collection.reference.to_string(),
-                price: self.collection.price,
+                price: self.placeholder.paid_price,
                 time_stamp: Clock::get()?.unix_timestamp,
+                model: provenance.model,
+                seed: provenance.seed,
+                prompt_hash: provenance.prompt_hash,
+                image_hash: provenance.image_hash,
+                mint: self.mint.key(),
+                revision: 0,
+                frozen: false,
+                rerolls: self.placeholder.rerolls,
+                attributes,
             }
         );
 
-        // Step 1: Initialize Account
-        let size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
-            &[
-                // ExtensionType::MintCloseAuthority,
-                ExtensionType::GroupMemberPointer,
-                // ExtensionType::PermanentDelegate,
-                ExtensionType::MetadataPointer,
-                // ExtensionType::TransferHook,
-            ],
-        ).unwrap();
-
-        let metadata = TokenMetadata {
-            update_authority: spl_pod::optional_keys::OptionalNonZeroPubkey::try_from(Some(self.auth.key())).unwrap(),
-            mint: self.mint.key(),
-            name: name.to_string(),
-            symbol: self.collection.symbol.to_string(),
-            uri,
-            additional_metadata: attributes.into_iter().map(|attr| (attr.key, attr.value)).collect(),
-        };
-
-        let extension_extra_space = metadata.tlv_size_of().unwrap();
-        let rent = &Rent::from_account_info(&self.rent.to_account_info())?;
-        let lamports = rent.minimum_balance(size + extension_extra_space);
-
-        let nft_key = self.nft.key();
-        let seeds: &[&[u8]; 3] = &[
-            b"mint",
-            nft_key.as_ref(),
-            &[bumps.mint],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        invoke_signed(
-            &solana_program::system_instruction::create_account(
-                &self.admin.key(),
-                &self.mint.key(),
-                lamports,
-                (size).try_into().unwrap(),
-                &spl_token_2022::id(),
-            ),
-            &vec![
-                self.admin.to_account_info(),
-                self.mint.to_account_info(),
-            ],
-            signer_seeds
-        )?;
-        
-        // 2.3: Add group member pointer
-        invoke(
-            &initialize_group_member_pointer(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                Some(self.auth.key()),
-                Some(self.collection_mint.key()), 
-            )?,
-            &vec![
-                self.mint.to_account_info(),
-            ],  
-        )?;
-        
-        // 2.4: Metadata Pointer
-        invoke(
-            &initialize_metadata_pointer(
-            &self.token_2022_program.key(),
-            &self.mint.key(),
-            Some(self.auth.key()),
-            Some(self.mint.key()),
-            )?,
-            &vec![
-            self.mint.to_account_info(),
-            ],
-        )?;
-
-        // Step 3: Initialize Mint & Metadata Account
-        invoke_signed(
-            &initialize_mint2(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-                None,
-                0,
-            )?,
-            &vec![
-                self.mint.to_account_info(),
-            ],
-            signer_seeds
-        )?;
-
-        let seeds: &[&[u8]; 2] = &[
-            b"auth",
-            &[bumps.auth],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        invoke_signed(
-            &initialize_metadata_account(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-                metadata.name,
-                metadata.symbol,
-                metadata.uri,
-            ),
-            &vec![
-                self.mint.to_account_info(),
-                self.auth.to_account_info(),
-            ],
-            signer_seeds
-        )?;
-
-        for (field, value) in metadata.additional_metadata.into_iter() {
-            invoke_signed(
-                &update_metadata_account(
-                    &self.token_2022_program.key(),
-                    &self.mint.key(),
-                    &self.auth.key(),
-                    Field::Key(field),
-                    value,
-                ),
-                &vec![
-                    self.mint.to_account_info(),
-                    self.auth.to_account_info(),
-                ],
-                signer_seeds
-            )?;
-        }
-
-       Ok(())
+        create_ai_nft_mint(
+            &AiNftMintAccounts {
+                payer: self.admin.to_account_info(),
+                nft: self.nft.key(),
+                mint: self.mint.to_account_info(),
+                collection_mint: self.collection_mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                rent: self.rent.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+            },
+            metadata,
+            self.collection.soulbound,
+            bumps.mint,
+            bumps.auth,
+        )
     }
-}
\ No newline at end of file
+}
@@ -1,25 +1,16 @@
 pub use anchor_lang::{
-    solana_program::{
-        sysvar::rent::ID as RENT_ID,
-        program::{invoke, invoke_signed}
-    },
+    solana_program::sysvar::rent::ID as RENT_ID,
     prelude::*
 };
 pub use anchor_spl::token_2022::Token2022;
-pub use spl_token_2022::{
-    extension::ExtensionType,
-    instruction::{initialize_mint_close_authority, initialize_permanent_delegate, initialize_mint2},
-    extension::metadata_pointer::instruction::initialize as initialize_metadata_pointer,
-};
-pub use spl_token_metadata_interface::{
-    state::{TokenMetadata, Field},
-    instruction::{initialize as initialize_metadata_account, update_field as update_metadata_account},
-};
-pub use crate::state::{Protocol, Collection, Admin, Placeholder};
+pub use crate::state::{Protocol, Collection, Admin, Placeholder, GenerationStatus};
+use crate::state::RevealMode;
 pub use crate::errors::{BuyingError, ProtocolError};
+use crate::moderation::check_text;
+use crate::placeholder::{PlaceholderMintAccounts, create_placeholder_mint, placeholder_metadata};
 
 #[derive(Accounts)]
-#[instruction(id: u64)]
+#[instruction(id: u64, uri: String)]
 pub struct CreatePlaceholder<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -38,7 +29,7 @@ pub struct CreatePlaceholder<'info> {
         payer = admin,
         seeds = [b"placeholder", collection.key().as_ref(), id.to_le_bytes().as_ref()],
         bump,
-        space = Placeholder::INIT_SPACE + 32 + collection.name.len() + collection.symbol.len() + 8 + 8,
+        space = Placeholder::INIT_SPACE + collection.name.len() + collection.max_prompt_len as usize + uri.len(),
     )] 
     pub placeholder: Account<'info, Placeholder>,
     /// CHECK: this is fine since we are handling all the checks and creation in the program.
@@ -48,6 +39,13 @@ pub struct CreatePlaceholder<'info> {
         bump
     )]
     pub mint: UncheckedAccount<'info>,
+    /// CHECK: the collection mint, TokenGroup of the InPlace placeholders
+    #[account(
+        mut,
+        seeds = [b"mint", collection.key().as_ref()],
+        bump,
+    )]
+    pub collection_mint: UncheckedAccount<'info>,
     /// CHECK:
     #[account(
         seeds = [b"auth"],
@@ -63,6 +61,18 @@ pub struct CreatePlaceholder<'info> {
         bump,
     )]
     pub protocol: Account<'info, Protocol>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_moderation: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -82,6 +92,9 @@ impl<'info> CreatePlaceholder<'info> {
 
             What these Instructions do:
             - Creates a placeholder NFT.
+            - In the InPlace reveal mode the mint is also added to the collection mint group (TokenGroupMember)
+            while the program still holds its mint authority, the reveal points its group member pointer to it.
+            - The placeholder serial (id) is kept for good, a reroll issues a new placeholder mint for it.
         */
 
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
@@ -97,158 +110,47 @@ impl<'info> CreatePlaceholder<'info> {
                 collection: self.collection.key(),  //32 bytes
                 reference: self.collection.reference.to_string(), // 4 + len
                 name: self.collection.name.clone(), // 4 + len
-                price: self.collection.price, // 8
-                time_stamp: Clock::get()?.unix_timestamp // 8
+                time_stamp: Clock::get()?.unix_timestamp, // 8
+                paid_price: 0, // 8, set on buy
+                status: GenerationStatus::Queued, // 1
+                prompt: String::new(), // 4 + max_prompt_len, set on buy
+                rerolls: 0, // 4
+                uri: uri.clone(), // 4 + len
+                count: self.collection.total_supply + 1, // 8
             }
         );
 
-        // Step 1: Initialize Account
-        let size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
-            &[
-                ExtensionType::MintCloseAuthority,
-                ExtensionType::PermanentDelegate,
-                ExtensionType::MetadataPointer,
-            ],
-        ).unwrap();
-        let _count = self.collection.total_supply + 1;
-        let metadata = TokenMetadata {
-            update_authority: spl_pod::optional_keys::OptionalNonZeroPubkey::try_from(Some(self.auth.key())).unwrap(),
-            mint: self.mint.key(),
-            name: "Placeholder for".to_string() + &self.collection.name,
-            symbol: self.collection.symbol.clone(),
+        let metadata = placeholder_metadata(
+            &self.collection,
+            self.mint.key(),
+            self.auth.key(),
+            id,
+            self.placeholder.count,
             uri,
-            additional_metadata: vec![
-                ("id".to_string(), id.to_string()),
-                ("count".to_string(), _count.to_string()),
-                ("timestamp".to_string(), Clock::get()?.unix_timestamp.to_string()),
-                ("price".to_string(), self.collection.price.to_string()),
-                ("collection".to_string(), self.collection.name.to_string()),
-                ("collection key".to_string(), self.collection.key().to_string())
-            ]
-        };
-
-        let extension_extra_space = metadata.tlv_size_of().unwrap();
-        let rent = &Rent::from_account_info(&self.rent.to_account_info())?;
-        let lamports = rent.minimum_balance(size + extension_extra_space);
-
-        let placeholder_key = self.placeholder.key();
-        let seeds: &[&[u8]; 3] = &[
-            b"mint",
-            placeholder_key.as_ref(),
-            &[bumps.mint],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        invoke_signed(
-            &solana_program::system_instruction::create_account(
-                &self.admin.key(),
-                &self.mint.key(),
-                lamports,
-                (size).try_into().unwrap(),
-                &spl_token_2022::id(),
-            ),
-            &vec![
-                self.admin.to_account_info(),
-                self.mint.to_account_info(),
-            ],
-            signer_seeds
-        )?;
-
-        // Step 2: Initialize Extension needed: 
-
-        // 2.1: Permanent Delegate, 
-        invoke(
-            &initialize_permanent_delegate(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-            )?,
-            &vec![
-                self.mint.to_account_info(),
-            ],
-        )?;
-        
-        // 2.2: Close Mint Authority, 
-        invoke(
-            &initialize_mint_close_authority(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                Some(&self.auth.key()),
-            )?,
-            &vec![
-                self.mint.to_account_info(),
-            ],
-        )?;
-        
-        // 2.3: Metadata Pointer
-        invoke(
-            &initialize_metadata_pointer(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                Some(self.auth.key()),
-                Some(self.mint.key()),
-            )?,
-            &vec![
-                self.mint.to_account_info(),
-            ],
         )?;
 
-        // Step 3: Initialize Mint & Metadata Account
-        invoke_signed(
-            &initialize_mint2(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-                None,
-                0,
-            )?,
-            &vec![
-                self.mint.to_account_info(),
-            ],
-            signer_seeds
+        check_text(
+            &[&self.protocol_moderation, &self.collection_moderation],
+            &[&metadata.name, &metadata.symbol],
         )?;
 
-        let seeds: &[&[u8]; 2] = &[
-            b"auth",
-            &[bumps.auth],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        invoke_signed(
-            &initialize_metadata_account(
-                &self.token_2022_program.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-                &self.mint.key(),
-                &self.auth.key(),
-                metadata.name,
-                metadata.symbol,
-                metadata.uri,
-            ),
-            &vec![
-                self.mint.to_account_info(),
-                self.auth.to_account_info(),
-            ],
-            signer_seeds
+        create_placeholder_mint(
+            &PlaceholderMintAccounts {
+                payer: self.admin.to_account_info(),
+                placeholder: self.placeholder.key(),
+                mint: self.mint.to_account_info(),
+                collection_mint: self.collection_mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                rent: self.rent.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+            },
+            metadata,
+            self.collection.reveal_mode == RevealMode::InPlace,
+            self.collection.soulbound,
+            bumps.mint,
+            bumps.auth,
         )?;
 
-        for (field, value) in metadata.additional_metadata.into_iter() {
-            invoke_signed(
-                &update_metadata_account(
-                    &self.token_2022_program.key(),
-                    &self.mint.key(),
-                    &self.auth.key(),
-                    Field::Key(field),
-                    value,
-                ),
-                &vec![
-                    self.mint.to_account_info(),
-                    self.auth.to_account_info(),
-                ],
-                signer_seeds
-            )?;
-        }
-
        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,282 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::sysvar::{rent::ID as RENT_ID, instructions},
+    },
+    anchor_spl::{
+        token_2022::Token2022,
+        associated_token::AssociatedToken,
+    },
+};
+use crate::state::{Protocol, Admin, AiNft, Attributes, Collection, Escrow, GenerationQueue, GenerationRequest, GenerationStatus, Placeholder, Provenance, RevealMode, TraitStats};
+use crate::errors::ProtocolError;
+use crate::escrow::release_sale;
+use crate::delivery::{AiNftMintAccounts, DeliveryAccounts, ai_nft_metadata, create_ai_nft_mint, deliver_ai_nft};
+
+#[derive(Accounts)]
+#[instruction(uri: String, name: String, attributes: Vec<Attributes>, provenance: Provenance)]
+pub struct Fulfill<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Box<Account<'info, Admin>>,
+    #[account(
+        mut,
+        seeds = [b"collection", collection.owner.key().as_ref()],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: this is fine since we are handling all the checks and creation in the program.
+    #[account(
+        seeds = [b"mint", collection.key().as_ref()],
+        bump,
+    )]
+    pub collection_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"placeholder", collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub placeholder: Box<Account<'info, Placeholder>>,
+    #[account(
+        mut,
+        seeds = [b"mint", placeholder.key().as_ref()],
+        bump
+    )]
+    /// CHECK
+    pub placeholder_mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"ainft", collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+        space = AiNft::space(&provenance.model, &attributes),
+    )]
+    pub nft: Box<Account<'info, AiNft>>,
+    /// CHECK: this is fine since we are handling all the checks and creation in the program.
+    #[account(
+        mut,
+        seeds = [b"mint", nft.key().as_ref()],
+        bump
+    )]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            buyer.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub buyer_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            buyer.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            placeholder_mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub buyer_placeholder_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<Box<Account<'info, Escrow>>>,
+    /// CHECK: receives the sale proceeds held in the escrow
+    #[account(
+        mut,
+        address = collection.owner,
+    )]
+    pub collection_owner: AccountInfo<'info>,
+    /// CHECK: checked against the escrow fee_recipient
+    #[account(mut)]
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        mut,
+        seeds = [b"request", collection.key().as_ref(), request.seq.to_le_bytes().as_ref()],
+        bump,
+        close = admin,
+        constraint = request.placeholder == placeholder.key() @ ProtocolError::RequestMismatch,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(address = RENT_ID)]
+    /// CHECK: this is fine since we are hard coding the rent sysvar.
+    pub rent: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Box<Account<'info, Protocol>>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection trait schema, may not be initialized
+    #[account(
+        seeds = [b"trait_schema", collection.key().as_ref()],
+        bump
+    )]
+    pub trait_schema: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TraitStats::INIT_SPACE,
+        seeds = [b"trait_stats", collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Box<Account<'info, TraitStats>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+    #[account(address = instructions::ID)]
+    /// CHECK: InstructionsSysvar account
+    pub instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> Fulfill<'info> {
+    pub fn fulfill(
+        &mut self,
+        uri: String,
+        name: String,
+        attributes: Vec<Attributes>,
+        provenance: Provenance,
+        bumps: FulfillBumps,
+    ) -> Result<()> {
+
+        /*
+
+            Fulfill Ix:
+
+            Some security check:
+            - The admin_state.publickey must match the signing admin, who must have claimed the GenerationRequest.
+            - The previous instruction must be an Ed25519 signature of the collection attestor over a Generation
+            message (collection, placeholder, sha256(uri), sha256(name), sha256(attributes), sha256(provenance)).
+
+            What these Instructions do:
+            - Runs create_nft & transfer_nft for one placeholder in a single instruction, so a failure
+            can't leave an AiNft mint without its delivery.
+            - The AiNft id is the placeholder id.
+
+            - Compute Budget
+                - The AiNft mint is funded for its full metadata when created, so no transfer happens per attribute.
+                - Every attribute costs an update_field CPI, they are capped at MAX_NFT_ATTRIBUTES so the
+                instruction stays under the 1.4M CU transaction limit. Clients should request a compute unit
+                limit with the ComputeBudget program and use an address lookup table for the accounts.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(self.collection.reveal_mode == RevealMode::Burn, ProtocolError::WrongRevealMode);
+        require!(self.request.claimed_by == Some(self.admin.key()), ProtocolError::RequestNotClaimed);
+
+        let metadata = ai_nft_metadata(
+            &self.collection,
+            self.placeholder.key(),
+            &self.instructions.to_account_info(),
+            &[&self.protocol_moderation, &self.collection_moderation],
+            &self.trait_schema,
+            self.mint.key(),
+            self.auth.key(),
+            uri,
+            name,
+            attributes.clone(),
+            provenance.clone(),
+        )?;
+
+        let collection_key = self.collection.key();
+        self.trait_stats.record(collection_key, &attributes)?;
+
+        self.nft.set_inner(
+            AiNft {
+                id: self.placeholder.id,
+                collection: self.collection.key(),
+                reference: self.collection.reference.to_string(),
+                price: self.placeholder.paid_price,
+                time_stamp: Clock::get()?.unix_timestamp,
+                model: provenance.model,
+                seed: provenance.seed,
+                prompt_hash: provenance.prompt_hash,
+                image_hash: provenance.image_hash,
+                mint: self.mint.key(),
+                revision: 0,
+                frozen: false,
+                rerolls: self.placeholder.rerolls,
+                attributes,
+            }
+        );
+
+        create_ai_nft_mint(
+            &AiNftMintAccounts {
+                payer: self.admin.to_account_info(),
+                nft: self.nft.key(),
+                mint: self.mint.to_account_info(),
+                collection_mint: self.collection_mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                rent: self.rent.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+            },
+            metadata,
+            self.collection.soulbound,
+            bumps.mint,
+            bumps.auth,
+        )?;
+
+        deliver_ai_nft(
+            &DeliveryAccounts {
+                payer: self.admin.to_account_info(),
+                buyer: self.buyer.to_account_info(),
+                buyer_mint_ata: self.buyer_mint_ata.to_account_info(),
+                mint: self.mint.to_account_info(),
+                buyer_placeholder_mint_ata: self.buyer_placeholder_mint_ata.to_account_info(),
+                placeholder_mint: self.placeholder_mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+            },
+            bumps.auth,
+        )?;
+
+        self.collection.mint_count += 1;
+
+        release_sale(self.escrow.as_deref(), self.placeholder.paid_price, &self.collection_owner, &self.fee_recipient)?;
+
+        self.placeholder.status = GenerationStatus::Delivered;
+        self.queue.completed += 1;
+
+        Ok(())
+    }
+}
@@ -19,6 +19,21 @@ pub use transfer_nft::*;
 pub mod create_nft;
 pub use create_nft::*;
 
+pub mod fulfill;
+pub use fulfill::*;
+
+pub mod reveal_placeholder;
+pub use reveal_placeholder::*;
+
+pub mod nft_metadata_setting;
+pub use nft_metadata_setting::*;
+
+pub mod nft_metadata_freeze;
+pub use nft_metadata_freeze::*;
+
+pub mod burn_nft;
+pub use burn_nft::*;
+
 pub mod create_placeholder;
 pub use create_placeholder::*;
 
@@ -26,4 +41,40 @@ pub mod buy_placeholder;
 pub use buy_placeholder::*;
 
 pub mod airdrop_placeholder;
-pub use airdrop_placeholder::*;
\ No newline at end of file
+pub use airdrop_placeholder::*;
+
+pub mod batch_airdrop;
+pub use batch_airdrop::*;
+
+pub mod quote_price;
+pub use quote_price::*;
+
+pub mod refund_placeholder;
+pub use refund_placeholder::*;
+
+pub mod update_generation_status;
+pub use update_generation_status::*;
+
+pub mod cancel_purchase;
+pub use cancel_purchase::*;
+
+pub mod claim_generation_request;
+pub use claim_generation_request::*;
+
+pub mod moderation_setting;
+pub use moderation_setting::*;
+
+pub mod moderation_removal;
+pub use moderation_removal::*;
+
+pub mod request_reroll;
+pub use request_reroll::*;
+
+pub mod trait_schema_setting;
+pub use trait_schema_setting::*;
+
+pub mod trait_schema_removal;
+pub use trait_schema_removal::*;
+
+pub mod rarity_score;
+pub use rarity_score::*;
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::{
+    state::{Admin, ModerationPolicy, Protocol},
+    errors::{ModerationError, ProtocolError},
+};
+
+#[derive(Accounts)]
+pub struct ModerationRemoval<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        mut,
+        seeds = [b"moderation", policy.scope.as_ref()],
+        bump,
+    )]
+    pub policy: Account<'info, ModerationPolicy>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+}
+
+impl<'info> ModerationRemoval<'info> {
+
+    /*
+
+        Remove Moderation Term Ix:
+
+        Some security check:
+        - The admin_state.publickey must match the signing admin.
+        - The policy must already exist, removing a term never creates one.
+
+        What these Instructions do:
+        - Remove a banned term from a moderation policy.
+    */
+
+    pub fn remove_term(
+        &mut self,
+        term: String,
+    ) -> Result<()> {
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+
+        let term = term.trim().to_lowercase();
+        let index = self.policy.banned.iter().position(|banned| *banned == term).ok_or(ModerationError::TermNotFound)?;
+        self.policy.banned.remove(index);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use crate::{
+    state::{Admin, ModerationPolicy, Protocol},
+    constant::{MAX_BANNED_TERMS, MAX_BANNED_TERM_LEN},
+    errors::{ModerationError, ProtocolError},
+};
+
+#[derive(Accounts)]
+#[instruction(scope: Pubkey)]
+pub struct ModerationSetting<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ModerationPolicy::INIT_SPACE,
+        seeds = [b"moderation", scope.as_ref()],
+        bump,
+    )]
+    pub policy: Account<'info, ModerationPolicy>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ModerationSetting<'info> {
+
+    /*
+        
+        Change Moderation Policy Ix:
+
+        Some security check:
+        - The admin_state.publickey must match the signing admin.
+
+        What these Instructions do:
+        - Add a banned (lowercase) term on a moderation policy, created on first use.
+        - The scope is the protocol account for the protocol wide policy, or a collection for its extension.
+        - create_collection (name, symbol, url & stable_id), create_placeholder, create_nft (and buyer prompts) reject text
+        containing a banned term.
+    */
+
+    pub fn add_term(
+        &mut self,
+        scope: Pubkey,
+        term: String,
+    ) -> Result<()> {
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+
+        let term = term.trim().to_lowercase();
+        require!(!term.is_empty() && term.len() <= MAX_BANNED_TERM_LEN, ModerationError::InvalidTerm);
+        require!(!self.policy.banned.contains(&term), ModerationError::TermAlreadyBanned);
+        require!(self.policy.banned.len() < MAX_BANNED_TERMS, ModerationError::TooManyTerms);
+
+        self.policy.scope = scope;
+        self.policy.banned.push(term);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::program::invoke_signed,
+    },
+    anchor_spl::{
+        token_2022::{Token2022, spl_token_2022::instruction::AuthorityType},
+        token_interface::{set_authority, SetAuthority},
+    },
+};
+use spl_pod::optional_keys::OptionalNonZeroPubkey;
+use spl_token_metadata_interface::instruction::update_authority;
+use crate::{
+    errors::ProtocolError,
+    state::{Admin, AiNft, Protocol},
+};
+
+#[derive(Accounts)]
+pub struct NftMetadataFreeze<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        mut,
+        seeds = [b"ainft", nft.collection.key().as_ref(), nft.id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub nft: Account<'info, AiNft>,
+    /// CHECK: the AiNft mint, holding its TokenMetadata
+    #[account(
+        mut,
+        address = nft.mint,
+    )]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> NftMetadataFreeze<'info> {
+
+    /*
+
+        Freeze AiNft Metadata Ix:
+
+        Some security check:
+        - The admin_state.publickey must match the signing admin.
+        - The AiNft metadata must not be frozen already.
+
+        What these Instructions do:
+        - Freeze the metadata, the update authority of the TokenMetadata (and of the metadata pointer)
+        is removed for good so it can never change again.
+    */
+
+    pub fn freeze_metadata(
+        &mut self,
+        bumps: NftMetadataFreezeBumps,
+    ) -> Result<()> {
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(!self.nft.frozen, ProtocolError::MetadataFrozen);
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        invoke_signed(
+            &update_authority(
+                &self.token_2022_program.key(),
+                &self.mint.key(),
+                &self.auth.key(),
+                OptionalNonZeroPubkey::try_from(None)?,
+            ),
+            &[
+                self.mint.to_account_info(),
+                self.auth.to_account_info(),
+            ],
+            signer_seeds
+        )?;
+
+        // otherwise the pointer could still be moved to another metadata account
+        set_authority(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                SetAuthority {
+                    current_authority: self.auth.to_account_info(),
+                    account_or_mint: self.mint.to_account_info(),
+                },
+                signer_seeds
+            ),
+            AuthorityType::MetadataPointer,
+            None
+        )?;
+
+        self.nft.frozen = true;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            program::invoke,
+            system_instruction,
+        },
+    },
+    anchor_spl::token_2022::Token2022,
+};
+use spl_token_metadata_interface::state::Field;
+use crate::{
+    constant::{MAX_NFT_ATTRIBUTES, PROVENANCE_METADATA_KEYS},
+    errors::ProtocolError,
+    metadata::{read_metadata, update_metadata_field},
+    moderation::check_text,
+    traits::check_attributes,
+    state::{Admin, AiNft, Attributes, Collection, Protocol, TraitStats},
+};
+
+#[derive(Accounts)]
+pub struct NftMetadataSetting<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        address = nft.collection,
+    )]
+    pub collection: Account<'info, Collection>,
+    #[account(
+        mut,
+        seeds = [b"ainft", nft.collection.key().as_ref(), nft.id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub nft: Account<'info, AiNft>,
+    #[account(
+        mut,
+        seeds = [b"trait_stats", collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Box<Account<'info, TraitStats>>,
+    /// CHECK: the AiNft mint, holding its TokenMetadata
+    #[account(
+        mut,
+        address = nft.mint,
+    )]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection trait schema, may not be initialized
+    #[account(
+        seeds = [b"trait_schema", collection.key().as_ref()],
+        bump
+    )]
+    pub trait_schema: UncheckedAccount<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> NftMetadataSetting<'info> {
+
+    /*
+
+        Change AiNft Metadata Ix:
+
+        Some security check:
+        - The admin_state.publickey must match the signing admin.
+        - The AiNft metadata must not be frozen.
+
+        What these Instructions do:
+        - Update the uri, name or attribute fields of an AiNft, at least one of them must differ from the current
+        metadata, fields passed with their current value are skipped. Every update increases the AiNft revision.
+        - Attributes are updated by key (or added) on the AiNft account as well, the admin funds
+        the account growth, the collection rarity counters follow the new attributes.
+        - The provenance keys can't be changed and the new texts (uri included) go through the moderation policies.
+        - The resulting attributes must conform to the collection trait schema, if it has one.
+    */
+
+    pub fn update_metadata(
+        &mut self,
+        uri: Option<String>,
+        name: Option<String>,
+        attributes: Vec<Attributes>,
+        bumps: NftMetadataSettingBumps,
+    ) -> Result<()> {
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(!self.nft.frozen, ProtocolError::MetadataFrozen);
+
+        // only the fields that differ from the current metadata are updated
+        let current = read_metadata(&self.mint.to_account_info())?;
+        let uri = uri.filter(|uri| *uri != current.uri);
+        let name = name.filter(|name| *name != current.name);
+        let attributes: Vec<Attributes> = attributes
+            .into_iter()
+            .filter(|attr| !self.nft.attributes.iter().any(|stored| stored.key == attr.key && stored.value == attr.value))
+            .collect();
+        require!(uri.is_some() || name.is_some() || !attributes.is_empty(), ProtocolError::EmptyMetadataUpdate);
+
+        require!(
+            !attributes.iter().any(|attr| PROVENANCE_METADATA_KEYS.contains(&attr.key.as_str())),
+            ProtocolError::ReservedAttributeKey
+        );
+
+        let mut texts: Vec<&str> = uri.iter().chain(name.iter()).map(|text| text.as_str()).collect();
+        texts.extend(attributes.iter().flat_map(|attr| [attr.key.as_str(), attr.value.as_str()]));
+        check_text(&[&self.protocol_moderation, &self.collection_moderation], &texts)?;
+
+        self.trait_stats.erase(&self.nft.attributes)?;
+        for attr in attributes.iter() {
+            match self.nft.attributes.iter_mut().find(|stored| stored.key == attr.key) {
+                Some(stored) => stored.value = attr.value.clone(),
+                None => self.nft.attributes.push(attr.clone()),
+            }
+        }
+        require!(self.nft.attributes.len() <= MAX_NFT_ATTRIBUTES, ProtocolError::TooManyAttributes);
+        check_attributes(&self.trait_schema, &self.nft.attributes)?;
+        let collection_key = self.collection.key();
+        self.trait_stats.record(collection_key, &self.nft.attributes)?;
+
+        let space = AiNft::space(&self.nft.model, &self.nft.attributes);
+        let nft_info = self.nft.to_account_info();
+        if space > nft_info.data_len() {
+            let lamports = Rent::get()?.minimum_balance(space).saturating_sub(nft_info.lamports());
+            if lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(&self.admin.key(), &nft_info.key(), lamports),
+                    &[
+                        self.admin.to_account_info(),
+                        nft_info.clone(),
+                        self.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+            nft_info.realloc(space, false)?;
+        }
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let fields = uri.map(|uri| (Field::Uri, uri))
+            .into_iter()
+            .chain(name.map(|name| (Field::Name, name)))
+            .chain(attributes.into_iter().map(|attr| (Field::Key(attr.key), attr.value)));
+        for (field, value) in fields {
+            update_metadata_field(
+                &self.token_2022_program.to_account_info(),
+                &self.mint.to_account_info(),
+                &self.auth.to_account_info(),
+                &self.admin.to_account_info(),
+                &self.system_program.to_account_info(),
+                field,
+                value,
+                signer_seeds,
+            )?;
+        }
+
+        self.nft.revision += 1;
+
+        Ok(())
+    }
+}
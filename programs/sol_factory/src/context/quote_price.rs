@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::Collection;
+
+#[derive(Accounts)]
+pub struct QuotePrice<'info> {
+    pub collection: Account<'info, Collection>,
+}
+
+impl<'info> QuotePrice<'info> {
+    pub fn quote(
+        &self,
+    ) -> Result<u64> {
+
+        /*
+        
+            Quote Price Ix:
+
+            What these Instructions do:
+            - Returns the price in lamports a buyer would pay right now (phase, dutch auction or bonding curve),
+            the value is sent back to the client through set_return_data.
+        */
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let fixed_price = match self.collection.active_phase(current_time)? {
+            Some(index) => self.collection.phases[index].price,
+            None => self.collection.price,
+        };
+
+        self.collection.current_price(current_time, fixed_price)
+    }
+}
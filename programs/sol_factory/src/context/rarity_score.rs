@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::state::{AiNft, TraitStats};
+
+#[derive(Accounts)]
+pub struct RarityScore<'info> {
+    pub nft: Account<'info, AiNft>,
+    #[account(
+        seeds = [b"trait_stats", nft.collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Account<'info, TraitStats>,
+}
+
+impl<'info> RarityScore<'info> {
+    pub fn score(
+        &self,
+    ) -> Result<u64> {
+
+        /*
+
+            Rarity Score Ix:
+
+            What these Instructions do:
+            - Returns the rarity score of an AiNft, the sum of (AiNfts in the collection / AiNfts sharing the trait)
+            over its attributes with 3 decimals (1_000 = 1.0), computed from the collection trait counters.
+            The value is sent back to the client through set_return_data.
+        */
+
+        self.trait_stats.rarity_score(&self.nft.attributes)
+    }
+}
@@ -0,0 +1,147 @@
+use {
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_2022::Token2022,
+        associated_token::AssociatedToken,
+        token_interface::{Burn, burn},
+    },
+};
+use crate::{
+    errors::{BuyingError, ProtocolError},
+    escrow::{cancel_escrow, deadline_passed},
+    state::{Collection, Escrow, GenerationQueue, GenerationRequest, MintRecord, Placeholder, Protocol},
+};
+
+#[derive(Accounts)]
+pub struct RefundPlaceholder<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"collection", collection.owner.key().as_ref()],
+        bump,
+    )] 
+    pub collection: Account<'info, Collection>,
+    #[account(
+        seeds = [b"placeholder", placeholder.collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = placeholder.collection == collection.key() @ BuyingError::CollectionMismatch,
+    )] 
+    pub placeholder: Account<'info, Placeholder>,
+    #[account(
+        mut,
+        seeds = [b"mint", placeholder.key().as_ref()],
+        bump
+    )]
+    /// CHECK
+    pub placeholder_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            holder.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            placeholder_mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub holder_placeholder_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: checked against the escrow fee_recipient
+    #[account(mut)]
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"mint_record", collection.key().as_ref(), escrow.buyer.as_ref()],
+        bump,
+    )]
+    pub mint_record: Option<Account<'info, MintRecord>>,
+    #[account(
+        mut,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        mut,
+        seeds = [b"request", collection.key().as_ref(), request.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = request.placeholder == placeholder.key() @ ProtocolError::RequestMismatch,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_2022_program: Program<'info, Token2022>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundPlaceholder<'info> {
+    pub fn refund(
+        &mut self,
+    ) -> Result<()> {
+
+        /*
+        
+            Refund Placeholder Ix:
+
+            Some security check:
+            - The delivery deadline of the escrow (buy time + collection delivery_window) must have passed.
+            - The signer must hold the placeholder.
+
+            What these Instructions do:
+            - Burns the Placeholder NFT of the holder.
+            - Gives the price paid held in the escrow back to the holder and closes the escrow, the rent goes back to
+            the fee recipient (the payer of the buy).
+            - Decrease the total_supply on the collection, the minted count of the sale phase and the MintRecord of the buyer
+            (required), unless the escrow holds a reroll fee.
+            - Closes the GenerationRequest if it was already claimed, otherwise flags it as cancelled,
+            either way it counts as completed.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(
+            deadline_passed(Clock::get()?.unix_timestamp, self.escrow.deadline),
+            BuyingError::DeliveryDeadlineNotReached
+        );
+
+        burn(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                Burn {
+                    mint: self.placeholder_mint.to_account_info(),
+                    from: self.holder_placeholder_mint_ata.to_account_info(),
+                    authority: self.holder.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        // a refund is a cancellation without fee
+        cancel_escrow(&self.escrow, &self.holder.to_account_info(), &self.fee_recipient, 0)?;
+
+        // a reroll fee was paid for a serial that is already counted, and stays so
+        if !self.escrow.reroll {
+            self.collection.unrecord_sale(self.escrow.phase)?;
+            self.mint_record.as_mut().ok_or(ProtocolError::MintRecordRequired)?.unrecord_mint(self.escrow.phase)?;
+        }
+
+        if self.request.claimed_by.is_some() {
+            self.request.close(self.holder.to_account_info())?;
+            self.queue.completed += 1;
+        } else {
+            self.queue.cancel(&mut self.request);
+        }
+
+        Ok(())
+    }
+}
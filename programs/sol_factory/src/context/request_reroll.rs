@@ -0,0 +1,322 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            sysvar::rent::ID as RENT_ID,
+            system_instruction,
+            program::invoke,
+        },
+    },
+    anchor_spl::{
+        token_2022::{
+            Token2022,
+            spl_token_2022::{
+                instruction::AuthorityType,
+                state::Account as TokenAccount,
+                extension::StateWithExtensions,
+            }},
+        associated_token::{AssociatedToken, Create, create_idempotent},
+        token_interface::{CloseAccount, close_account, MintTo, mint_to, set_authority, SetAuthority},
+    },
+};
+use crate::{
+    errors::{BuyingError, ProtocolError},
+    state::{AiNft, Collection, Escrow, GenerationQueue, GenerationRequest, GenerationStatus, Placeholder, Protocol, RevealMode, TraitStats},
+    delivery::{BurnAccounts, burn_ai_nft},
+    escrow::escrow_deadline,
+    placeholder::{PlaceholderMintAccounts, create_placeholder_mint, placeholder_metadata},
+};
+
+#[derive(Accounts)]
+pub struct RequestReroll<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    #[account(
+        mut,
+        address = nft.collection,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        mut,
+        seeds = [b"ainft", nft.collection.key().as_ref(), nft.id.to_le_bytes().as_ref()],
+        bump,
+        close = holder,
+    )]
+    pub nft: Box<Account<'info, AiNft>>,
+    #[account(
+        mut,
+        seeds = [b"trait_stats", collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Box<Account<'info, TraitStats>>,
+    /// CHECK: the AiNft mint, the placeholder mint itself for InPlace reveals
+    #[account(
+        mut,
+        address = nft.mint,
+    )]
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            holder.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub holder_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"placeholder", collection.key().as_ref(), nft.id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub placeholder: Box<Account<'info, Placeholder>>,
+    /// CHECK: this is fine since we are handling all the checks and creation in the program.
+    #[account(
+        mut,
+        seeds = [b"mint", placeholder.key().as_ref()],
+        bump
+    )]
+    pub placeholder_mint: UncheckedAccount<'info>,
+    /// CHECK: the collection mint, TokenGroup of the InPlace placeholders
+    #[account(
+        mut,
+        seeds = [b"mint", collection.key().as_ref()],
+        bump,
+    )]
+    pub collection_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            holder.key().as_ref(),
+            token_2022_program.key().as_ref(),
+            placeholder_mint.key().as_ref()
+        ],
+        seeds::program = associated_token_program.key(),
+        bump
+    )]
+    /// CHECK
+    pub holder_placeholder_mint_ata: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = holder,
+        space = Escrow::INIT_SPACE,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    #[account(
+        mut,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        init,
+        payer = holder,
+        space = GenerationRequest::INIT_SPACE + collection.stable_id.len(),
+        seeds = [b"request", collection.key().as_ref(), queue.tail.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(address = RENT_ID)]
+    /// CHECK: this is fine since we are hard coding the rent sysvar.
+    pub rent: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Box<Account<'info, Protocol>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RequestReroll<'info> {
+    pub fn reroll(
+        &mut self,
+        bumps: RequestRerollBumps,
+    ) -> Result<()> {
+
+        /*
+
+            Request Reroll Ix:
+
+            Some security check:
+            - The holder of the AiNft must sign, the burn fails if their ATA doesn't hold the token.
+            - The collection must have a reroll fee, a 0 fee means rerolls are disabled.
+
+            What these Instructions do:
+            - Pays the reroll fee into a new escrow of the placeholder, released to the collection owner
+            when the new AiNft is delivered or refunded like a sale after the delivery deadline.
+            - Burns the AiNft and closes its mint & account (see burn_nft), the rent goes back to the holder,
+            its traits are uncounted from the rarity counters.
+            - Closes the burned placeholder mint and creates a fresh one at the same address, the serial (placeholder
+            id) stays the same. For InPlace reveals the AiNft mint is the placeholder mint, it's only closed once.
+            - Mints the new placeholder to the holder, with the original uri, count & prompt, and queues a GenerationRequest.
+            - The reroll escrow is flagged, refunding or cancelling it doesn't take the serial back from the supply.
+            - Increases the placeholder rerolls, recorded on the next AiNft of the serial.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.collection.reroll_fee > 0, BuyingError::RerollDisabled);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let reroll_fee = self.collection.reroll_fee;
+
+        invoke(
+            &system_instruction::transfer(
+                &self.holder.key(),
+                &self.escrow.key(),
+                reroll_fee,
+            ),
+            &[
+                self.holder.to_account_info(),
+                self.escrow.to_account_info(),
+                self.system_program.to_account_info(),
+            ],
+        )?;
+
+        // the whole fee goes to the collection owner, the escrow rent goes back to the holder
+        self.escrow.set_inner(Escrow {
+            placeholder: self.placeholder.key(),
+            buyer: self.holder.key(),
+            fee_recipient: self.holder.key(),
+            amount: reroll_fee,
+            fee: 0,
+            deadline: escrow_deadline(current_time, self.collection.delivery_window)?,
+            phase: None,
+            reroll: true,
+        });
+
+        burn_ai_nft(
+            &BurnAccounts {
+                holder: self.holder.to_account_info(),
+                holder_mint_ata: self.holder_mint_ata.to_account_info(),
+                mint: self.mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+            },
+            bumps.auth,
+        )?;
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // the placeholder was burned on delivery, closing it frees the address for the new one
+        if self.mint.key() != self.placeholder_mint.key() {
+            close_account(
+                CpiContext::new_with_signer(
+                    self.token_2022_program.to_account_info(),
+                    CloseAccount {
+                        account: self.placeholder_mint.to_account_info(),
+                        destination: self.holder.to_account_info(),
+                        authority: self.auth.to_account_info(),
+                    },
+                    signer_seeds
+                ),
+            )?;
+        }
+
+        let mut metadata = placeholder_metadata(
+            &self.collection,
+            self.placeholder_mint.key(),
+            self.auth.key(),
+            self.placeholder.id,
+            self.placeholder.count,
+            self.placeholder.uri.clone(),
+        )?;
+        if !self.placeholder.prompt.is_empty() {
+            metadata.additional_metadata.push(("prompt".to_string(), self.placeholder.prompt.clone()));
+        }
+
+        create_placeholder_mint(
+            &PlaceholderMintAccounts {
+                payer: self.holder.to_account_info(),
+                placeholder: self.placeholder.key(),
+                mint: self.placeholder_mint.to_account_info(),
+                collection_mint: self.collection_mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                rent: self.rent.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
+            },
+            metadata,
+            self.collection.reveal_mode == RevealMode::InPlace,
+            self.collection.soulbound,
+            bumps.placeholder_mint,
+            bumps.auth,
+        )?;
+
+        // the holder ATA of the previous placeholder mint can still be open with a 0 balance
+        create_idempotent(
+            CpiContext::new(
+                self.token_2022_program.to_account_info(),
+                Create {
+                    payer: self.holder.to_account_info(),
+                    associated_token: self.holder_placeholder_mint_ata.to_account_info(),
+                    authority: self.holder.to_account_info(),
+                    mint: self.placeholder_mint.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_2022_program.to_account_info(),
+                }
+            ),
+        )?;
+
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                MintTo {
+                    mint: self.placeholder_mint.to_account_info(),
+                    to: self.holder_placeholder_mint_ata.to_account_info(),
+                    authority: self.auth.to_account_info(),
+                },
+                signer_seeds
+            ),
+            1,
+        )?;
+
+        set_authority(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                SetAuthority {
+                    current_authority: self.auth.to_account_info(),
+                    account_or_mint: self.placeholder_mint.to_account_info(),
+                },
+                signer_seeds
+            ),
+            AuthorityType::MintTokens,
+            None
+        )?;
+
+        // check the post balance of the mint
+        {
+            let _after_data = self.holder_placeholder_mint_ata.data.borrow();
+            let _after_state = StateWithExtensions::<TokenAccount>::unpack(&_after_data)?;
+
+            require!(_after_state.base.amount == 1, ProtocolError::InvalidBalancePostMint);
+        }
+
+        self.collection.mint_count = self.collection.mint_count.checked_sub(1).ok_or(ProtocolError::CounterUnderflow)?;
+        self.trait_stats.erase(&self.nft.attributes)?;
+
+        self.placeholder.paid_price = reroll_fee;
+        self.placeholder.status = GenerationStatus::Queued;
+        self.placeholder.rerolls += 1;
+
+        let (collection_key, placeholder_key) = (self.collection.key(), self.placeholder.key());
+        self.queue.enqueue(&mut self.request, &self.collection, collection_key, placeholder_key, self.holder.key())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use {
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            program::invoke_signed,
+            sysvar::instructions,
+        },
+    },
+    anchor_spl::{
+        token_2022::{Token2022, spl_token_2022::instruction::AuthorityType},
+        token_interface::{set_authority, SetAuthority},
+    },
+};
+use spl_token_2022::extension::group_member_pointer::instruction::update as update_group_member_pointer;
+use spl_token_metadata_interface::state::Field;
+use crate::state::{Protocol, Admin, AiNft, Attributes, Collection, Escrow, GenerationQueue, GenerationRequest, GenerationStatus, Placeholder, Provenance, RevealMode, TraitStats};
+use crate::errors::ProtocolError;
+use crate::escrow::release_sale;
+use crate::delivery::ai_nft_metadata;
+use crate::metadata::update_metadata_field;
+
+#[derive(Accounts)]
+#[instruction(uri: String, name: String, attributes: Vec<Attributes>, provenance: Provenance)]
+pub struct RevealPlaceholder<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Box<Account<'info, Admin>>,
+    #[account(
+        mut,
+        seeds = [b"collection", collection.owner.key().as_ref()],
+        bump,
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: this is fine since we are handling all the checks and creation in the program.
+    #[account(
+        seeds = [b"mint", collection.key().as_ref()],
+        bump,
+    )]
+    pub collection_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"placeholder", collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub placeholder: Box<Account<'info, Placeholder>>,
+    #[account(
+        mut,
+        seeds = [b"mint", placeholder.key().as_ref()],
+        bump
+    )]
+    /// CHECK
+    pub placeholder_mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"ainft", collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+        space = AiNft::space(&provenance.model, &attributes),
+    )]
+    pub nft: Box<Account<'info, AiNft>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<Box<Account<'info, Escrow>>>,
+    /// CHECK: receives the sale proceeds held in the escrow
+    #[account(
+        mut,
+        address = collection.owner,
+    )]
+    pub collection_owner: AccountInfo<'info>,
+    /// CHECK: checked against the escrow fee_recipient
+    #[account(mut)]
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        mut,
+        seeds = [b"request", collection.key().as_ref(), request.seq.to_le_bytes().as_ref()],
+        bump,
+        close = admin,
+        constraint = request.placeholder == placeholder.key() @ ProtocolError::RequestMismatch,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
+    /// CHECK:
+    #[account(
+        seeds = [b"auth"],
+        bump
+    )]
+    pub auth: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Box<Account<'info, Protocol>>,
+    /// CHECK: protocol wide moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", protocol.key().as_ref()],
+        bump
+    )]
+    pub protocol_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection moderation policy, may not be initialized
+    #[account(
+        seeds = [b"moderation", collection.key().as_ref()],
+        bump
+    )]
+    pub collection_moderation: UncheckedAccount<'info>,
+    /// CHECK: collection trait schema, may not be initialized
+    #[account(
+        seeds = [b"trait_schema", collection.key().as_ref()],
+        bump
+    )]
+    pub trait_schema: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TraitStats::INIT_SPACE,
+        seeds = [b"trait_stats", collection.key().as_ref()],
+        bump,
+    )]
+    pub trait_stats: Box<Account<'info, TraitStats>>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+    #[account(address = instructions::ID)]
+    /// CHECK: InstructionsSysvar account
+    pub instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> RevealPlaceholder<'info> {
+    pub fn reveal(
+        &mut self,
+        uri: String,
+        name: String,
+        attributes: Vec<Attributes>,
+        provenance: Provenance,
+        bumps: RevealPlaceholderBumps,
+    ) -> Result<()> {
+
+        /*
+
+            Reveal Placeholder Ix:
+
+            Some security check:
+            - The collection must use the InPlace reveal mode.
+            - The admin_state.publickey must match the signing admin, who must have claimed the GenerationRequest.
+            - The previous instruction must be an Ed25519 signature of the collection attestor over a Generation
+            message (collection, placeholder, sha256(uri), sha256(name), sha256(attributes), sha256(provenance)).
+
+            What these Instructions do:
+            - Rewrites the placeholder mint metadata (name, uri, attributes & provenance) through the auth
+            update authority, the token the buyer holds becomes the AI NFT.
+            - Attaches the NFT to the collection group: points the mint group member pointer to the collection mint,
+            whose TokenGroup already holds the TokenGroupMember added when the placeholder mint was created.
+            - Removes the permanent delegate, the revealed NFT can't be burned or moved by the program anymore.
+            - Stores the AiNft record of the placeholder (the AiNft id is the placeholder id).
+            - Releases the escrowed sale, marks the Placeholder as Delivered and closes the GenerationRequest.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.collection.reveal_mode == RevealMode::InPlace, ProtocolError::WrongRevealMode);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(self.request.claimed_by == Some(self.admin.key()), ProtocolError::RequestNotClaimed);
+
+        let metadata = ai_nft_metadata(
+            &self.collection,
+            self.placeholder.key(),
+            &self.instructions.to_account_info(),
+            &[&self.protocol_moderation, &self.collection_moderation],
+            &self.trait_schema,
+            self.placeholder_mint.key(),
+            self.auth.key(),
+            uri,
+            name,
+            attributes.clone(),
+            provenance.clone(),
+        )?;
+
+        let collection_key = self.collection.key();
+        self.trait_stats.record(collection_key, &attributes)?;
+
+        self.nft.set_inner(
+            AiNft {
+                id: self.placeholder.id,
+                collection: self.collection.key(),
+                reference: self.collection.reference.to_string(),
+                price: self.placeholder.paid_price,
+                time_stamp: Clock::get()?.unix_timestamp,
+                model: provenance.model,
+                seed: provenance.seed,
+                prompt_hash: provenance.prompt_hash,
+                image_hash: provenance.image_hash,
+                mint: self.placeholder_mint.key(),
+                revision: 0,
+                frozen: false,
+                rerolls: self.placeholder.rerolls,
+                attributes,
+            }
+        );
+
+        let seeds: &[&[u8]; 2] = &[
+            b"auth",
+            &[bumps.auth],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let fields = [
+            (Field::Name, metadata.name),
+            (Field::Uri, metadata.uri),
+        ].into_iter().chain(
+            metadata.additional_metadata.into_iter().map(|(key, value)| (Field::Key(key), value))
+        );
+        for (field, value) in fields {
+            update_metadata_field(
+                &self.token_2022_program.to_account_info(),
+                &self.placeholder_mint.to_account_info(),
+                &self.auth.to_account_info(),
+                &self.admin.to_account_info(),
+                &self.system_program.to_account_info(),
+                field,
+                value,
+                signer_seeds,
+            )?;
+        }
+
+        // the TokenGroupMember was initialized with the mint, it needs the mint authority given up on sale
+        invoke_signed(
+            &update_group_member_pointer(
+                &self.token_2022_program.key(),
+                &self.placeholder_mint.key(),
+                &self.auth.key(),
+                &[],
+                Some(self.collection_mint.key()),
+            )?,
+            &[
+                self.placeholder_mint.to_account_info(),
+                self.auth.to_account_info(),
+            ],
+            signer_seeds
+        )?;
+
+        set_authority(
+            CpiContext::new_with_signer(
+                self.token_2022_program.to_account_info(),
+                SetAuthority {
+                    current_authority: self.auth.to_account_info(),
+                    account_or_mint: self.placeholder_mint.to_account_info(),
+                },
+                signer_seeds
+            ),
+            AuthorityType::PermanentDelegate,
+            None
+        )?;
+
+        self.collection.mint_count += 1;
+
+        release_sale(self.escrow.as_deref(), self.placeholder.paid_price, &self.collection_owner, &self.fee_recipient)?;
+
+        self.placeholder.status = GenerationStatus::Delivered;
+        self.queue.completed += 1;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::{
+    state::{Admin, Collection, Protocol, TraitSchema},
+    errors::{ProtocolError, TraitSchemaError},
+};
+
+#[derive(Accounts)]
+pub struct TraitSchemaRemoval<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        seeds = [b"collection", collection.owner.key().as_ref()],
+        bump,
+    )]
+    pub collection: Account<'info, Collection>,
+    #[account(
+        mut,
+        seeds = [b"trait_schema", collection.key().as_ref()],
+        bump,
+    )]
+    pub schema: Box<Account<'info, TraitSchema>>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+}
+
+impl<'info> TraitSchemaRemoval<'info> {
+
+    /*
+
+        Remove Trait Ix:
+
+        Some security check:
+        - The admin_state.publickey must match the signing admin.
+        - The schema must already exist, removing a trait never creates one.
+
+        What these Instructions do:
+        - Remove a trait from the collection schema.
+        - Removing the last trait closes the schema (rent back to the admin), the collection accepts any
+        attributes again instead of rejecting every key.
+    */
+
+    pub fn remove_trait(
+        &mut self,
+        key: String,
+    ) -> Result<()> {
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+
+        let index = self.schema.traits.iter().position(|current| current.key == key).ok_or(TraitSchemaError::TraitNotFound)?;
+        self.schema.traits.remove(index);
+
+        if self.schema.traits.is_empty() {
+            self.schema.close(self.admin.to_account_info())?;
+        }
+
+        Ok(())
+    }
+}
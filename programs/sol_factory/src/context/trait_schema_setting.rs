@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use crate::{
+    state::{Admin, Collection, Protocol, TraitDefinition, TraitRule, TraitSchema},
+    constant::{MAX_SCHEMA_TRAITS, MAX_TRAIT_KEY_LEN, MAX_TRAIT_VALUES, MAX_TRAIT_VALUE_LEN, PROVENANCE_METADATA_KEYS},
+    errors::{ProtocolError, TraitSchemaError},
+};
+
+#[derive(Accounts)]
+pub struct TraitSchemaSetting<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        seeds = [b"collection", collection.owner.key().as_ref()],
+        bump,
+    )]
+    pub collection: Account<'info, Collection>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TraitSchema::INIT_SPACE,
+        seeds = [b"trait_schema", collection.key().as_ref()],
+        bump,
+    )]
+    pub schema: Box<Account<'info, TraitSchema>>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TraitSchemaSetting<'info> {
+
+    /*
+
+        Change Trait Schema Ix:
+
+        Some security check:
+        - The admin_state.publickey must match the signing admin.
+
+        What these Instructions do:
+        - Add a trait to the collection schema, created on first use, or replace the definition of an existing key.
+        - Once a collection has a schema, the AiNft attributes (create_nft, fulfill, reveal_placeholder
+        and update_nft_metadata) must use its keys, match its value rules and hold every required trait.
+    */
+
+    pub fn set_trait(
+        &mut self,
+        definition: TraitDefinition,
+    ) -> Result<()> {
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+
+        require!(
+            !definition.key.is_empty()
+                && definition.key.len() <= MAX_TRAIT_KEY_LEN
+                && !PROVENANCE_METADATA_KEYS.contains(&definition.key.as_str()),
+            TraitSchemaError::InvalidTraitDefinition
+        );
+        if let TraitRule::OneOf { values } = &definition.rule {
+            require!(
+                !values.is_empty()
+                    && values.len() <= MAX_TRAIT_VALUES
+                    && values.iter().all(|value| value.len() <= MAX_TRAIT_VALUE_LEN),
+                TraitSchemaError::InvalidTraitDefinition
+            );
+        }
+
+        self.schema.collection = self.collection.key();
+        match self.schema.traits.iter_mut().find(|current| current.key == definition.key) {
+            Some(current) => *current = definition,
+            None => {
+                require!(self.schema.traits.len() < MAX_SCHEMA_TRAITS, TraitSchemaError::TooManyTraits);
+                self.schema.traits.push(definition);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,24 +1,15 @@
 use {
-    anchor_lang::{
-        prelude::*,
-        solana_program::program::invoke_signed
-    },
+    anchor_lang::prelude::*,
     anchor_spl::{
-        token_2022::{
-            Token2022, 
-            spl_token_2022::{
-                instruction::AuthorityType,
-                state::Account as TokenAccount,
-                extension::StateWithExtensions,
-            }},
-        associated_token::{AssociatedToken, Create, create},  
+        token_2022::Token2022,
+        associated_token::AssociatedToken,
         token::Token,
-        token_interface::{MintTo, mint_to, set_authority, SetAuthority}
     },
 };
-use spl_token_2022::instruction::burn;
-use crate::state::{Protocol, AiNft, Collection, Placeholder};
+use crate::state::{Protocol, AiNft, Collection, Escrow, GenerationQueue, GenerationRequest, GenerationStatus, Placeholder};
 use crate::errors::ProtocolError;
+use crate::escrow::release_sale;
+use crate::delivery::{DeliveryAccounts, deliver_ai_nft};
 
 #[derive(Accounts)]
 pub struct TransferNft<'info> {
@@ -41,7 +32,7 @@ pub struct TransferNft<'info> {
     pub buyer_mint_ata: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [b"ainft", nft.collection.key().as_ref(), nft.id.to_le_bytes().as_ref()],
+        seeds = [b"ainft", collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
         bump,
     )] 
     pub nft: Account<'info, AiNft>,
@@ -78,7 +69,7 @@ pub struct TransferNft<'info> {
     pub buyer_placeholder_mint_ata: AccountInfo<'info>,
     #[account(
         mut,
-        seeds = [b"placeholder", placeholder.collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        seeds = [b"placeholder", collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
         bump,
     )] 
     pub placeholder: Account<'info, Placeholder>,
@@ -89,7 +80,35 @@ pub struct TransferNft<'info> {
     )]
     /// CHECK
     pub placeholder_mint: UncheckedAccount<'info>,
-    pub placeholder_mint_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow", placeholder.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    /// CHECK: receives the sale proceeds held in the escrow
+    #[account(
+        mut,
+        address = collection.owner,
+    )]
+    pub collection_owner: AccountInfo<'info>,
+    /// CHECK: checked against the escrow fee_recipient
+    #[account(mut)]
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"queue", collection.key().as_ref()],
+        bump,
+    )]
+    pub queue: Box<Account<'info, GenerationQueue>>,
+    #[account(
+        mut,
+        seeds = [b"request", collection.key().as_ref(), request.seq.to_le_bytes().as_ref()],
+        bump,
+        close = payer,
+        constraint = request.placeholder == placeholder.key() @ ProtocolError::RequestMismatch,
+    )]
+    pub request: Box<Account<'info, GenerationRequest>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
     pub token_2022_program: Program<'info, Token2022>,
@@ -112,134 +131,43 @@ impl<'info> TransferNft<'info> {
             Transfer Nft Ix:
 
             Some security check:
-            - The authority of the burn instruction must be admin of the protocol.
+            - The payer must be the admin that claimed the GenerationRequest of the Placeholder.
+            - The Placeholder & AiNft are derived from the collection and the Placeholder id, the delivered AiNft is the one of
+            this serial.
 
             What these Instructions do:
             - Initialize the transfer of the created Ai NFT.
             - Burn the Placeholder NFT.
+            - Release the escrowed sale proceeds to the collection owner & admin (bought placeholders only).
+            - Mark the Placeholder generation as Delivered.
+            - Close the claimed GenerationRequest of the Placeholder.
         */
 
         // Check if the protocol is locked, if it is, return an error
         require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.request.claimed_by == Some(self.payer.key()), ProtocolError::RequestNotClaimed);
 
-        let seeds: &[&[u8]; 2] = &[
-            b"auth",
-            &[bumps.auth],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        // Initialize ATA if it doesn't exist
-        if self.buyer_mint_ata.owner != &self.buyer.key() {
-            create(
-            CpiContext::new(
-                self.token_2022_program.to_account_info(),
-                Create {
-                payer: self.payer.to_account_info(), // payer
-                associated_token: self.buyer_mint_ata.to_account_info(),
-                authority: self.buyer.to_account_info(), // owner
+        deliver_ai_nft(
+            &DeliveryAccounts {
+                payer: self.payer.to_account_info(),
+                buyer: self.buyer.to_account_info(),
+                buyer_mint_ata: self.buyer_mint_ata.to_account_info(),
                 mint: self.mint.to_account_info(),
+                buyer_placeholder_mint_ata: self.buyer_placeholder_mint_ata.to_account_info(),
+                placeholder_mint: self.placeholder_mint.to_account_info(),
+                auth: self.auth.to_account_info(),
+                token_2022_program: self.token_2022_program.to_account_info(),
                 system_program: self.system_program.to_account_info(),
-                token_program: self.token_2022_program.to_account_info(),
-                }
-            ),
-            )?;
-        }
-        
-        // balance before minting
-        {
-            let _before_data = self.buyer_mint_ata.data.borrow();
-            let _before_state = StateWithExtensions::<TokenAccount>::unpack(&_before_data)?;
-            
-            // msg!("before mint balance={}", _before_state.base.amount);
-
-            require!(
-                _before_state.base.amount == 0,
-                ProtocolError::InvalidBalancePreMint
-            );
-        }
-        
-
-        // Mint the mint
-        mint_to(
-            CpiContext::new_with_signer(
-                self.token_2022_program.to_account_info(),
-                MintTo {
-                    mint: self.mint.to_account_info(),
-                    to: self.buyer_mint_ata.to_account_info(),
-                    authority: self.auth.to_account_info(),
-                },
-                signer_seeds
-            ),
-            1,
+            },
+            bumps.auth,
         )?;
 
-        set_authority(
-            CpiContext::new_with_signer(
-                self.token_2022_program.to_account_info(), 
-                SetAuthority {
-                    current_authority: self.auth.to_account_info(),
-                    account_or_mint: self.mint.to_account_info(),
-                }, 
-                signer_seeds
-            ), 
-            AuthorityType::MintTokens, 
-            None
-        )?;
-
-        // balance after minting, reload the data
-        {
-            let _after_data = self.buyer_mint_ata.data.borrow();
-            let _after_state = StateWithExtensions::<TokenAccount>::unpack(&_after_data)?;
-
-            // msg!("after mint balance={}", _after_state.base.amount);
-
-            require!(_after_state.base.amount == 1, ProtocolError::InvalidBalancePostMint);
-        }
-
         self.collection.mint_count += 1; 
 
-        {
-            let _before_burn_data = self.buyer_placeholder_mint_ata.data.borrow();
-            let _before_burn_state = StateWithExtensions::<TokenAccount>::unpack(&_before_burn_data)?;
-
-            require!(_before_burn_state.base.amount > 0, ProtocolError::InvalidBalancePreBurn);
-
-            // msg!("before burn balance={}", _before_burn_state.base.amount);
-        }
-
-
-        // Burn the placeholder nft
-        let ix = burn(
-            &self.token_2022_program.key,
-            self.buyer_placeholder_mint_ata.to_account_info().key,
-            self.placeholder_mint.key,
-            &self.auth.key,
-            &[&self.auth.key()],
-            1,
-        )?;
-
-        invoke_signed(
-            &ix,
-            &[
-                self.buyer_placeholder_mint_ata.to_account_info(),
-                self.placeholder_mint.to_account_info(),
-                self.placeholder_mint_authority.to_account_info(),
-                self.auth.to_account_info(),
-                self.token_2022_program.to_account_info(),
-                self.system_program.to_account_info(),
-            ],
-            &[&seeds[..]],
-        )?;
-
-        // check the post balance of the burn
-        {
-            let _after_burn_data = self.buyer_placeholder_mint_ata.data.borrow();   
-            let _after_burn_state = StateWithExtensions::<TokenAccount>::unpack(&_after_burn_data)?;
-
-            // msg!("after burn balance={}", _after_burn_state.base.amount);
+        release_sale(self.escrow.as_ref(), self.placeholder.paid_price, &self.collection_owner, &self.fee_recipient)?;
 
-            require!(_after_burn_state.base.amount == 0, ProtocolError::InvalidBalancePostBurn);
-        }
+        self.placeholder.status = GenerationStatus::Delivered;
+        self.queue.completed += 1;
         
         Ok(())
     }
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::{
+    state::{Admin, GenerationStatus, Placeholder, Protocol},
+    errors::ProtocolError,
+};
+
+#[derive(Accounts)]
+pub struct UpdateGenerationStatus<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"admin_state", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_state: Account<'info, Admin>,
+    #[account(
+        mut,
+        seeds = [b"placeholder", placeholder.collection.key().as_ref(), placeholder.id.to_le_bytes().as_ref()],
+        bump,
+    )] 
+    pub placeholder: Account<'info, Placeholder>,
+    #[account(
+        seeds = [b"protocol"],
+        bump,
+    )]
+    pub protocol: Account<'info, Protocol>,
+}
+
+impl<'info> UpdateGenerationStatus<'info> {
+    pub fn update(
+        &mut self,
+        status: GenerationStatus,
+    ) -> Result<()> {
+
+        /*
+        
+            Update Generation Status Ix:
+
+            Some security check:
+            - The admin_state.publickey must match the signing admin (fulfilment admin).
+            - The status can only move forward: Queued -> Generating -> Delivered.
+
+            What these Instructions do:
+            - Advances the generation status of the Placeholder, once Generating the buyer can't cancel anymore.
+        */
+
+        require!(!self.protocol.locked, ProtocolError::ProtocolLocked);
+        require!(self.admin_state.publickey == *self.admin.key, ProtocolError::UnauthorizedAdmin);
+        require!(status > self.placeholder.status, ProtocolError::InvalidStatusTransition);
+
+        self.placeholder.status = status;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,436 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        hash::hash,
+        program::{invoke, invoke_signed},
+        system_instruction,
+    },
+};
+use anchor_spl::{
+    associated_token::{Create, create_idempotent},
+    token_2022::spl_token_2022::{
+        instruction::AuthorityType,
+        state::Account as TokenAccount,
+    },
+    token_interface::{self, Burn, CloseAccount, close_account, MintTo, mint_to, set_authority, SetAuthority},
+};
+use spl_token_2022::{
+    extension::{ExtensionType, StateWithExtensions},
+    extension::metadata_pointer::instruction::initialize as initialize_metadata_pointer,
+    extension::group_member_pointer::instruction::initialize as initialize_group_member_pointer,
+    instruction::{burn, initialize_mint2, initialize_mint_close_authority, initialize_non_transferable_mint},
+};
+use spl_token_metadata_interface::{
+    state::{TokenMetadata, Field},
+    instruction::{initialize as initialize_metadata_account, update_field as update_metadata_account},
+};
+use crate::{
+    constant::{MAX_NFT_ATTRIBUTES, MODEL_METADATA_KEY, SEED_METADATA_KEY, PROMPT_HASH_METADATA_KEY, IMAGE_HASH_METADATA_KEY, PROVENANCE_METADATA_KEYS},
+    ed25519::{SignedPayload, verify_signed_payload},
+    errors::ProtocolError,
+    metadata::to_hex,
+    moderation::check_text,
+    traits::check_attributes,
+    state::{Attributes, Collection, Provenance},
+};
+
+/*
+    Checks a new AiNft before it's minted and returns its TokenMetadata.
+
+    - The previous instruction must be an Ed25519 signature of the collection attestor over a Generation
+    message (collection, placeholder, sha256(uri), sha256(name), sha256(attributes), sha256(provenance)).
+    - The name and attributes must pass the protocol & collection moderation.
+    - The attributes must conform to the collection trait schema, if it has one.
+    - The provenance model must be the collection stable_id and attributes can't use the provenance keys.
+    - At most MAX_NFT_ATTRIBUTES attributes, they are stored on the AiNft account too.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn ai_nft_metadata(
+    collection: &Account<Collection>,
+    placeholder: Pubkey,
+    instructions: &AccountInfo,
+    moderation: &[&AccountInfo],
+    trait_schema: &AccountInfo,
+    mint: Pubkey,
+    auth: Pubkey,
+    uri: String,
+    name: String,
+    attributes: Vec<Attributes>,
+    provenance: Provenance,
+) -> Result<TokenMetadata> {
+    // attributes & provenance are hashed in their borsh serialized form
+    let attestation = SignedPayload::Generation {
+        collection: collection.key(),
+        placeholder,
+        uri_hash: hash(uri.as_bytes()).to_bytes(),
+        name_hash: hash(name.as_bytes()).to_bytes(),
+        attributes_hash: hash(&attributes.try_to_vec()?).to_bytes(),
+        provenance_hash: hash(&provenance.try_to_vec()?).to_bytes(),
+    };
+    verify_signed_payload(instructions, &collection.attestor, &attestation)?;
+
+    let mut texts: Vec<&str> = vec![&name];
+    texts.extend(attributes.iter().flat_map(|attr| [attr.key.as_str(), attr.value.as_str()]));
+    check_text(moderation, &texts)?;
+    check_attributes(trait_schema, &attributes)?;
+
+    require!(provenance.model == collection.stable_id, ProtocolError::ModelMismatch);
+    require!(attributes.len() <= MAX_NFT_ATTRIBUTES, ProtocolError::TooManyAttributes);
+    require!(
+        !attributes.iter().any(|attr| PROVENANCE_METADATA_KEYS.contains(&attr.key.as_str())),
+        ProtocolError::ReservedAttributeKey
+    );
+
+    Ok(TokenMetadata {
+        update_authority: spl_pod::optional_keys::OptionalNonZeroPubkey::try_from(Some(auth)).unwrap(),
+        mint,
+        name,
+        symbol: collection.symbol.to_string(),
+        uri,
+        additional_metadata: attributes
+            .into_iter()
+            .map(|attr| (attr.key, attr.value))
+            .chain([
+                (MODEL_METADATA_KEY.to_string(), provenance.model),
+                (SEED_METADATA_KEY.to_string(), provenance.seed.to_string()),
+                (PROMPT_HASH_METADATA_KEY.to_string(), to_hex(&provenance.prompt_hash)),
+                (IMAGE_HASH_METADATA_KEY.to_string(), to_hex(&provenance.image_hash)),
+            ])
+            .collect(),
+    })
+}
+
+pub struct AiNftMintAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub nft: Pubkey,
+    pub mint: AccountInfo<'info>,
+    pub collection_mint: AccountInfo<'info>,
+    pub auth: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+}
+
+/*
+    Creates the Token-2022 mint of an AiNft, member of the collection group, with its metadata stored on the mint.
+    The mint of a soulbound collection is NonTransferable, the AiNft can only be burned by its holder.
+
+    The mint is funded for the full metadata up front, so the update_field calls adding the
+    attributes don't need any extra transfer.
+*/
+pub fn create_ai_nft_mint(accounts: &AiNftMintAccounts, metadata: TokenMetadata, soulbound: bool, mint_bump: u8, auth_bump: u8) -> Result<()> {
+    // Step 1: Initialize Account
+    let mut extensions = vec![
+        ExtensionType::MintCloseAuthority,
+        ExtensionType::GroupMemberPointer,
+        // ExtensionType::PermanentDelegate,
+        ExtensionType::MetadataPointer,
+        // ExtensionType::TransferHook,
+    ];
+    if soulbound {
+        extensions.push(ExtensionType::NonTransferable);
+    }
+    let size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions).unwrap();
+
+    let extension_extra_space = metadata.tlv_size_of().unwrap();
+    let rent = &Rent::from_account_info(&accounts.rent)?;
+    let lamports = rent.minimum_balance(size + extension_extra_space);
+
+    let seeds: &[&[u8]; 3] = &[
+        b"mint",
+        accounts.nft.as_ref(),
+        &[mint_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    invoke_signed(
+        &system_instruction::create_account(
+            accounts.payer.key,
+            accounts.mint.key,
+            lamports,
+            (size).try_into().unwrap(),
+            &spl_token_2022::id(),
+        ),
+        &[
+            accounts.payer.clone(),
+            accounts.mint.clone(),
+        ],
+        signer_seeds
+    )?;
+
+    // 2.2: Close Mint Authority, lets burn_nft reclaim the mint rent
+    invoke(
+        &initialize_mint_close_authority(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            Some(accounts.auth.key),
+        )?,
+        std::slice::from_ref(&accounts.mint),
+    )?;
+
+    // 2.3: Add group member pointer
+    invoke(
+        &initialize_group_member_pointer(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            Some(accounts.auth.key()),
+            Some(accounts.collection_mint.key()),
+        )?,
+        std::slice::from_ref(&accounts.mint),
+    )?;
+
+    // 2.4: Metadata Pointer
+    invoke(
+        &initialize_metadata_pointer(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            Some(accounts.auth.key()),
+            Some(accounts.mint.key()),
+        )?,
+        std::slice::from_ref(&accounts.mint),
+    )?;
+
+    // 2.5: Non Transferable, soulbound collections only
+    if soulbound {
+        invoke(
+            &initialize_non_transferable_mint(
+                accounts.token_2022_program.key,
+                accounts.mint.key,
+            )?,
+            std::slice::from_ref(&accounts.mint),
+        )?;
+    }
+
+    // Step 3: Initialize Mint & Metadata Account
+    invoke_signed(
+        &initialize_mint2(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            accounts.auth.key,
+            None,
+            0,
+        )?,
+        std::slice::from_ref(&accounts.mint),
+        signer_seeds
+    )?;
+
+    let seeds: &[&[u8]; 2] = &[
+        b"auth",
+        &[auth_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    invoke_signed(
+        &initialize_metadata_account(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            accounts.auth.key,
+            accounts.mint.key,
+            accounts.auth.key,
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        ),
+        &[
+            accounts.mint.clone(),
+            accounts.auth.clone(),
+        ],
+        signer_seeds
+    )?;
+
+    for (field, value) in metadata.additional_metadata.into_iter() {
+        invoke_signed(
+            &update_metadata_account(
+                accounts.token_2022_program.key,
+                accounts.mint.key,
+                accounts.auth.key,
+                Field::Key(field),
+                value,
+            ),
+            &[
+                accounts.mint.clone(),
+                accounts.auth.clone(),
+            ],
+            signer_seeds
+        )?;
+    }
+
+    Ok(())
+}
+
+pub struct DeliveryAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub buyer: AccountInfo<'info>,
+    pub buyer_mint_ata: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub buyer_placeholder_mint_ata: AccountInfo<'info>,
+    pub placeholder_mint: AccountInfo<'info>,
+    pub auth: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/*
+    Mints the AiNft to the buyer and burns the placeholder NFT they hold.
+
+    - The buyer ATA is created if it doesn't exist and must be empty before the mint.
+    - The mint authority is removed so the AiNft supply stays at 1.
+    - The placeholder is burned by the auth PDA, its mint permanent delegate.
+*/
+pub fn deliver_ai_nft(accounts: &DeliveryAccounts, auth_bump: u8) -> Result<()> {
+    let seeds: &[&[u8]; 2] = &[
+        b"auth",
+        &[auth_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    // Initialize ATA if it doesn't exist
+    create_idempotent(
+        CpiContext::new(
+            accounts.token_2022_program.clone(),
+            Create {
+                payer: accounts.payer.clone(), // payer
+                associated_token: accounts.buyer_mint_ata.clone(),
+                authority: accounts.buyer.clone(), // owner
+                mint: accounts.mint.clone(),
+                system_program: accounts.system_program.clone(),
+                token_program: accounts.token_2022_program.clone(),
+            }
+        ),
+    )?;
+
+    // balance before minting
+    {
+        let _before_data = accounts.buyer_mint_ata.data.borrow();
+        let _before_state = StateWithExtensions::<TokenAccount>::unpack(&_before_data)?;
+
+        // msg!("before mint balance={}", _before_state.base.amount);
+
+        require!(
+            _before_state.base.amount == 0,
+            ProtocolError::InvalidBalancePreMint
+        );
+    }
+
+    // Mint the mint
+    mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_2022_program.clone(),
+            MintTo {
+                mint: accounts.mint.clone(),
+                to: accounts.buyer_mint_ata.clone(),
+                authority: accounts.auth.clone(),
+            },
+            signer_seeds
+        ),
+        1,
+    )?;
+
+    set_authority(
+        CpiContext::new_with_signer(
+            accounts.token_2022_program.clone(),
+            SetAuthority {
+                current_authority: accounts.auth.clone(),
+                account_or_mint: accounts.mint.clone(),
+            },
+            signer_seeds
+        ),
+        AuthorityType::MintTokens,
+        None
+    )?;
+
+    // balance after minting, reload the data
+    {
+        let _after_data = accounts.buyer_mint_ata.data.borrow();
+        let _after_state = StateWithExtensions::<TokenAccount>::unpack(&_after_data)?;
+
+        // msg!("after mint balance={}", _after_state.base.amount);
+
+        require!(_after_state.base.amount == 1, ProtocolError::InvalidBalancePostMint);
+    }
+
+    {
+        let _before_burn_data = accounts.buyer_placeholder_mint_ata.data.borrow();
+        let _before_burn_state = StateWithExtensions::<TokenAccount>::unpack(&_before_burn_data)?;
+
+        require!(_before_burn_state.base.amount > 0, ProtocolError::InvalidBalancePreBurn);
+
+        // msg!("before burn balance={}", _before_burn_state.base.amount);
+    }
+
+    // Burn the placeholder nft
+    let ix = burn(
+        accounts.token_2022_program.key,
+        accounts.buyer_placeholder_mint_ata.key,
+        accounts.placeholder_mint.key,
+        accounts.auth.key,
+        &[accounts.auth.key],
+        1,
+    )?;
+
+    invoke_signed(
+        &ix,
+        &[
+            accounts.buyer_placeholder_mint_ata.clone(),
+            accounts.placeholder_mint.clone(),
+            accounts.auth.clone(),
+            accounts.token_2022_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+
+    // check the post balance of the burn
+    {
+        let _after_burn_data = accounts.buyer_placeholder_mint_ata.data.borrow();
+        let _after_burn_state = StateWithExtensions::<TokenAccount>::unpack(&_after_burn_data)?;
+
+        // msg!("after burn balance={}", _after_burn_state.base.amount);
+
+        require!(_after_burn_state.base.amount == 0, ProtocolError::InvalidBalancePostBurn);
+    }
+
+    Ok(())
+}
+
+pub struct BurnAccounts<'info> {
+    pub holder: AccountInfo<'info>,
+    pub holder_mint_ata: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub auth: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+}
+
+/*
+    Burns the AiNft the holder signs for and closes its mint through the MintCloseAuthority (the auth PDA),
+    the mint rent goes back to the holder. The holder ATA is left open.
+*/
+pub fn burn_ai_nft(accounts: &BurnAccounts, auth_bump: u8) -> Result<()> {
+    token_interface::burn(
+        CpiContext::new(
+            accounts.token_2022_program.clone(),
+            Burn {
+                mint: accounts.mint.clone(),
+                from: accounts.holder_mint_ata.clone(),
+                authority: accounts.holder.clone(),
+            },
+        ),
+        1,
+    )?;
+
+    let seeds: &[&[u8]; 2] = &[
+        b"auth",
+        &[auth_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    close_account(
+        CpiContext::new_with_signer(
+            accounts.token_2022_program.clone(),
+            CloseAccount {
+                account: accounts.mint.clone(),
+                destination: accounts.holder.clone(),
+                authority: accounts.auth.clone(),
+            },
+            signer_seeds
+        ),
+    )
+}
@@ -0,0 +1,288 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+};
+use std::str::FromStr;
+use crate::{
+    constant::{ED25519_PROGRAM_ID, SIGNED_MESSAGE_DOMAIN, SIGNED_MESSAGE_VERSION},
+    errors::ProtocolError,
+};
+
+// Layout of the Ed25519 program instruction data
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+const PUBKEY_SERIALIZED_SIZE: usize = 32;
+const SIGNATURE_SERIALIZED_SIZE: usize = 64;
+
+// Offsets of a single signature, an instruction index of u16::MAX means the data is in the Ed25519 instruction itself
+struct Ed25519SignatureOffsets {
+    signature_offset: u16,
+    signature_instruction_index: u16,
+    public_key_offset: u16,
+    public_key_instruction_index: u16,
+    message_data_offset: u16,
+    message_data_size: u16,
+    message_instruction_index: u16,
+}
+
+impl Ed25519SignatureOffsets {
+    fn unpack(data: &[u8]) -> Self {
+        let field = |index: usize| -> u16 {
+            let start = SIGNATURE_OFFSETS_START + index * 2;
+            u16::from_le_bytes([data[start], data[start + 1]])
+        };
+
+        Self {
+            signature_offset: field(0),
+            signature_instruction_index: field(1),
+            public_key_offset: field(2),
+            public_key_instruction_index: field(3),
+            message_data_offset: field(4),
+            message_data_size: field(5),
+            message_instruction_index: field(6),
+        }
+    }
+}
+
+// Payloads the off-chain signers can sign, the variant index is part of the message so a signature only fits one use
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub enum SignedPayload {
+    Airdrop { collection: Pubkey, buyer: Pubkey, placeholder: Pubkey, expiry: i64, nonce: u64 },
+    Generation { collection: Pubkey, placeholder: Pubkey, uri_hash: [u8; 32], name_hash: [u8; 32], attributes_hash: [u8; 32], provenance_hash: [u8; 32] },
+    AirdropBatch { collection: Pubkey, merkle_root: [u8; 32], expiry: i64, nonce: u64 },
+}
+
+impl SignedPayload {
+    // Signed messages are domain tag || version || borsh(payload)
+    pub fn to_message(&self) -> Result<Vec<u8>> {
+        let mut message = SIGNED_MESSAGE_DOMAIN.to_vec();
+        message.push(SIGNED_MESSAGE_VERSION);
+        self.serialize(&mut message)?;
+
+        Ok(message)
+    }
+}
+
+// Checks that the previous instruction is an Ed25519 signature of `signer` over the payload
+pub fn verify_signed_payload(instructions: &AccountInfo, signer: &Pubkey, payload: &SignedPayload) -> Result<()> {
+    let signature_ix = load_previous_ed25519_ix(instructions)?;
+    verify_ed25519_ix(&signature_ix, signer, &payload.to_message()?)
+}
+
+// Same as verify_signed_payload when any of the `signers` may have signed, returns the one that did
+pub fn verify_signed_payload_by_any(instructions: &AccountInfo, signers: &[Pubkey], payload: &SignedPayload) -> Result<Pubkey> {
+    let signature_ix = load_previous_ed25519_ix(instructions)?;
+    let (public_key, signed_message) = parse_ed25519_ix(&signature_ix)?;
+
+    require!(signed_message == payload.to_message()?.as_slice(), ProtocolError::InvalidSignedMessage);
+
+    signers
+        .iter()
+        .find(|signer| public_key == signer.as_ref())
+        .copied()
+        .ok_or(ProtocolError::InvalidSigner.into())
+}
+
+// Returns the instruction right before the current one, it must be an Ed25519 program instruction
+pub fn load_previous_ed25519_ix(instructions: &AccountInfo) -> Result<Instruction> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    require!(current_index > 0, ProtocolError::InstructionsNotCorrect);
+
+    let signature_ix = load_instruction_at_checked(current_index - 1, instructions)
+        .map_err(|_| ProtocolError::InstructionsNotCorrect)?;
+    require!(
+        signature_ix.program_id == Pubkey::from_str(ED25519_PROGRAM_ID).unwrap(),
+        ProtocolError::InstructionsNotCorrect
+    );
+
+    Ok(signature_ix)
+}
+
+// Checks that the Ed25519 instruction verified exactly one signature of `signer` over `message`, byte for byte
+pub fn verify_ed25519_ix(signature_ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let (public_key, signed_message) = parse_ed25519_ix(signature_ix)?;
+
+    require!(public_key == signer.as_ref(), ProtocolError::InvalidSigner);
+    require!(signed_message == message, ProtocolError::InvalidSignedMessage);
+
+    Ok(())
+}
+
+/*
+    Returns the public key and message of the single signature verified by the Ed25519 instruction.
+
+    - The instruction must carry a single signature.
+    - The signature, public key and message must all live in the Ed25519 instruction itself,
+    otherwise the verified data could be read from an instruction we don't look at.
+    - Every offset must be in bounds.
+*/
+fn parse_ed25519_ix(signature_ix: &Instruction) -> Result<(&[u8], &[u8])> {
+    let data = &signature_ix.data;
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SERIALIZED_SIZE,
+        ProtocolError::InvalidSignatureInstruction
+    );
+    require!(data[0] == 1, ProtocolError::InvalidSignatureInstruction);
+
+    let offsets = Ed25519SignatureOffsets::unpack(data);
+    require!(
+        offsets.signature_instruction_index == u16::MAX
+            && offsets.public_key_instruction_index == u16::MAX
+            && offsets.message_instruction_index == u16::MAX,
+        ProtocolError::InvalidSignatureInstruction
+    );
+
+    let slice = |offset: u16, size: usize| -> Result<&[u8]> {
+        let start = offset as usize;
+        data.get(start..start + size).ok_or(ProtocolError::InvalidSignatureInstruction.into())
+    };
+
+    slice(offsets.signature_offset, SIGNATURE_SERIALIZED_SIZE)?;
+    let public_key = slice(offsets.public_key_offset, PUBKEY_SERIALIZED_SIZE)?;
+    let signed_message = slice(offsets.message_data_offset, offsets.message_data_size as usize)?;
+
+    Ok((public_key, signed_message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA_START: usize = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+
+    // Same layout as the Ed25519 program client: offsets, then public key, signature and message
+    fn ed25519_ix_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let public_key_offset = DATA_START;
+        let signature_offset = public_key_offset + PUBKEY_SERIALIZED_SIZE;
+        let message_data_offset = signature_offset + SIGNATURE_SERIALIZED_SIZE;
+
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset as u16,
+            u16::MAX,
+            public_key_offset as u16,
+            u16::MAX,
+            message_data_offset as u16,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7; SIGNATURE_SERIALIZED_SIZE]);
+        data.extend_from_slice(message);
+
+        data
+    }
+
+    fn ed25519_ix(data: Vec<u8>) -> Instruction {
+        Instruction {
+            program_id: Pubkey::from_str(ED25519_PROGRAM_ID).unwrap(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    fn set_field(data: &mut [u8], index: usize, value: u16) {
+        let start = SIGNATURE_OFFSETS_START + index * 2;
+        data[start..start + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn payload() -> SignedPayload {
+        SignedPayload::Airdrop {
+            collection: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            placeholder: Pubkey::new_unique(),
+            expiry: 100,
+            nonce: 1,
+        }
+    }
+
+    fn assert_error<T: std::fmt::Debug>(result: Result<T>, error: ProtocolError) {
+        assert_eq!(result.unwrap_err(), error.into());
+    }
+
+    #[test]
+    fn accepts_a_signature_of_the_signer_over_the_message() {
+        let signer = Pubkey::new_unique();
+        let message = payload().to_message().unwrap();
+
+        assert!(verify_ed25519_ix(&ed25519_ix(ed25519_ix_data(&signer, &message)), &signer, &message).is_ok());
+    }
+
+    #[test]
+    fn signed_messages_are_domain_and_version_tagged() {
+        let message = payload().to_message().unwrap();
+
+        assert!(message.starts_with(SIGNED_MESSAGE_DOMAIN));
+        assert_eq!(message[SIGNED_MESSAGE_DOMAIN.len()], SIGNED_MESSAGE_VERSION);
+        // Airdrop is the first variant
+        assert_eq!(message[SIGNED_MESSAGE_DOMAIN.len() + 1], 0);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let signer = Pubkey::new_unique();
+        let message = payload().to_message().unwrap();
+        let data = ed25519_ix_data(&signer, &message);
+
+        // cut inside of the offsets
+        assert_error(verify_ed25519_ix(&ed25519_ix(data[..DATA_START - 1].to_vec()), &signer, &message), ProtocolError::InvalidSignatureInstruction);
+        assert_error(verify_ed25519_ix(&ed25519_ix(vec![]), &signer, &message), ProtocolError::InvalidSignatureInstruction);
+        // cut inside of the public key, the signature and the message
+        for len in [DATA_START + 10, DATA_START + PUBKEY_SERIALIZED_SIZE + 10, data.len() - 1] {
+            assert_error(verify_ed25519_ix(&ed25519_ix(data[..len].to_vec()), &signer, &message), ProtocolError::InvalidSignatureInstruction);
+        }
+    }
+
+    #[test]
+    fn rejects_anything_but_a_single_signature() {
+        let signer = Pubkey::new_unique();
+        let message = payload().to_message().unwrap();
+
+        for count in [0, 2] {
+            let mut data = ed25519_ix_data(&signer, &message);
+            data[0] = count;
+            assert_error(verify_ed25519_ix(&ed25519_ix(data), &signer, &message), ProtocolError::InvalidSignatureInstruction);
+        }
+    }
+
+    #[test]
+    fn rejects_data_read_from_other_instructions() {
+        let signer = Pubkey::new_unique();
+        let message = payload().to_message().unwrap();
+
+        // signature, public key and message instruction indexes
+        for index in [1, 3, 6] {
+            let mut data = ed25519_ix_data(&signer, &message);
+            set_field(&mut data, index, 0);
+            assert_error(verify_ed25519_ix(&ed25519_ix(data), &signer, &message), ProtocolError::InvalidSignatureInstruction);
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_offsets() {
+        let signer = Pubkey::new_unique();
+        let message = payload().to_message().unwrap();
+
+        // signature, public key and message offsets, then the message size
+        for (index, value) in [(0, u16::MAX), (2, u16::MAX), (4, u16::MAX), (5, message.len() as u16 + 1)] {
+            let mut data = ed25519_ix_data(&signer, &message);
+            set_field(&mut data, index, value);
+            assert_error(verify_ed25519_ix(&ed25519_ix(data), &signer, &message), ProtocolError::InvalidSignatureInstruction);
+        }
+    }
+
+    #[test]
+    fn rejects_another_signer_or_message() {
+        let signer = Pubkey::new_unique();
+        let message = payload().to_message().unwrap();
+        let ix = ed25519_ix(ed25519_ix_data(&signer, &message));
+
+        assert_error(verify_ed25519_ix(&ix, &Pubkey::new_unique(), &message), ProtocolError::InvalidSigner);
+        assert_error(verify_ed25519_ix(&ix, &signer, &payload().to_message().unwrap()), ProtocolError::InvalidSignedMessage);
+    }
+}
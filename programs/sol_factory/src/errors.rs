@@ -20,6 +20,26 @@ pub enum BuyingError {
     WalletDoesNotMatch,
     #[msg("Token account doesn't match the expected mint")]
     TokenAccountMismatch,
+    #[msg("The current sale phase is sold out!")]
+    PhaseSoldOut,
+    #[msg("You reached the mint limit for the current sale phase!")]
+    PhaseWalletLimitReached,
+    #[msg("The placeholder does not belong to this collection")]
+    CollectionMismatch,
+    #[msg("This wallet reached the mint limit of the collection!")]
+    WalletLimitReached,
+    #[msg("The delivery deadline has not passed yet")]
+    DeliveryDeadlineNotReached,
+    #[msg("The generation already started, the purchase can't be cancelled")]
+    GenerationAlreadyStarted,
+    #[msg("This collection doesn't accept prompts")]
+    PromptDisabled,
+    #[msg("The prompt is too long")]
+    PromptTooLong,
+    #[msg("The collection airdrop reserve is used up")]
+    AirdropReserveExhausted,
+    #[msg("This collection doesn't allow rerolls")]
+    RerollDisabled,
 }
 #[error_code]
 pub enum ProtocolError {
@@ -35,6 +55,62 @@ pub enum ProtocolError {
     InvalidMaxSupply,
     #[msg("Invalid Price")]
     InvalidPrice,
+    #[msg("Invalid Sale Phase")]
+    InvalidSalePhase,
+    #[msg("Invalid Pricing Mode")]
+    InvalidPricingMode,
+    #[msg("Price computation overflowed")]
+    PriceOverflow,
+    #[msg("The collection counters can't go below 0")]
+    CounterUnderflow,
+    #[msg("Invalid Delivery Window")]
+    InvalidDeliveryWindow,
+    #[msg("The escrow of a paid placeholder is required")]
+    EscrowRequired,
+    #[msg("The mint record of the buyer is required")]
+    MintRecordRequired,
+    #[msg("The fee recipient does not match the escrow")]
+    InvalidFeeRecipient,
+    #[msg("Invalid Cancellation Fee")]
+    InvalidCancellationFee,
+    #[msg("The escrow fee is larger than the escrowed amount")]
+    InvalidEscrowFee,
+    #[msg("The generation status can only move forward")]
+    InvalidStatusTransition,
+    #[msg("Generation requests have to be claimed in order")]
+    RequestOutOfOrder,
+    #[msg("The generation request has not been claimed")]
+    RequestNotClaimed,
+    #[msg("The generation request does not match the placeholder")]
+    RequestMismatch,
+    #[msg("The model does not match the collection stable_id")]
+    ModelMismatch,
+    #[msg("The attribute key is reserved for the generation provenance")]
+    ReservedAttributeKey,
+    #[msg("The collection needs a generator attestor")]
+    InvalidAttestor,
+    #[msg("The airdrop reserve can't be larger than the max supply")]
+    InvalidAirdropReserve,
+    #[msg("The batch accounts or proofs don't match the signed recipient list")]
+    InvalidBatch,
+    #[msg("Too many attributes to fit in the compute budget")]
+    TooManyAttributes,
+    #[msg("The instruction isn't available in the collection reveal mode")]
+    WrongRevealMode,
+    #[msg("The AiNft metadata is frozen")]
+    MetadataFrozen,
+    #[msg("The metadata update doesn't change anything")]
+    EmptyMetadataUpdate,
+    #[msg("The Ed25519 instruction is malformed")]
+    InvalidSignatureInstruction,
+    #[msg("The Ed25519 instruction wasn't signed by the expected signer")]
+    InvalidSigner,
+    #[msg("The signed message doesn't match")]
+    InvalidSignedMessage,
+    #[msg("The airdrop voucher has expired")]
+    VoucherExpired,
+    #[msg("The airdrop voucher was already used")]
+    VoucherAlreadyUsed,
     #[msg("Mint Count did not increment")]
     InvalidMintCount,
     #[msg("Invalid Balance of Token Pre Mint")]
@@ -47,4 +123,34 @@ pub enum ProtocolError {
     InvalidBalancePreBurn,
     #[msg("Invalid balance post burn")]
     InvalidBalancePostBurn,
-}
\ No newline at end of file
+}
+#[error_code]
+pub enum ModerationError {
+    #[msg("The text contains a banned term")]
+    BannedText,
+    #[msg("The banned term is empty or too long")]
+    InvalidTerm,
+    #[msg("The moderation policy is full")]
+    TooManyTerms,
+    #[msg("The term is already banned")]
+    TermAlreadyBanned,
+    #[msg("The term is not in the moderation policy")]
+    TermNotFound,
+}
+#[error_code]
+pub enum TraitSchemaError {
+    #[msg("The attribute key is not in the collection trait schema")]
+    UnknownTrait,
+    #[msg("A required trait of the collection is missing")]
+    MissingRequiredTrait,
+    #[msg("The attribute value doesn't match the trait rule")]
+    InvalidTraitValue,
+    #[msg("The attribute key is used more than once")]
+    DuplicateTrait,
+    #[msg("The trait definition is empty or too long")]
+    InvalidTraitDefinition,
+    #[msg("The trait schema is full")]
+    TooManyTraits,
+    #[msg("The trait is not in the trait schema")]
+    TraitNotFound,
+}
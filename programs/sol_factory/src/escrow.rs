@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use crate::{
+    constant::BPS_DENOMINATOR,
+    errors::ProtocolError,
+    state::Escrow,
+};
+
+// The escrow is owned by the program, so lamports can be moved out of it directly
+fn pay_out(escrow: &AccountInfo, destination: &AccountInfo, amount: u64) -> Result<()> {
+    **escrow.try_borrow_mut_lamports()? -= amount;
+    **destination.try_borrow_mut_lamports()? += amount;
+
+    Ok(())
+}
+
+// The holder can refund the escrow once the delivery window has passed
+pub fn escrow_deadline(current_time: i64, delivery_window: i64) -> Result<i64> {
+    current_time.checked_add(delivery_window).ok_or(ProtocolError::InvalidDeliveryWindow.into())
+}
+
+pub fn deadline_passed(current_time: i64, deadline: i64) -> bool {
+    current_time > deadline
+}
+
+// Collection owner share of a released escrow, the admin fee stays in the escrow
+fn release_split(amount: u64, fee: u64) -> Result<u64> {
+    amount.checked_sub(fee).ok_or(ProtocolError::InvalidEscrowFee.into())
+}
+
+// (refund, cancellation fee) of a cancelled escrow, the fee rounds down in favor of the holder
+fn cancellation_split(amount: u64, cancellation_fee_bps: u16) -> Result<(u64, u64)> {
+    require!(u64::from(cancellation_fee_bps) <= BPS_DENOMINATOR, ProtocolError::InvalidCancellationFee);
+
+    let cancellation_fee = u128::from(amount) * u128::from(cancellation_fee_bps) / u128::from(BPS_DENOMINATOR);
+    let cancellation_fee = u64::try_from(cancellation_fee).map_err(|_| ProtocolError::InvalidCancellationFee)?;
+
+    Ok((amount - cancellation_fee, cancellation_fee))
+}
+
+/*
+    Pays the collection owner out of the escrow once the AiNft is delivered, closing the escrow
+    sends the admin fee and the rent to the fee recipient (the payer of the buy).
+*/
+fn release_escrow<'info>(
+    escrow: &Account<'info, Escrow>,
+    collection_owner: &AccountInfo<'info>,
+    fee_recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(fee_recipient.key() == escrow.fee_recipient, ProtocolError::InvalidFeeRecipient);
+
+    pay_out(&escrow.to_account_info(), collection_owner, release_split(escrow.amount, escrow.fee)?)?;
+
+    escrow.close(fee_recipient.clone())
+}
+
+/*
+    Pays out the sale of a delivered placeholder, airdropped placeholders don't have an escrow
+    so they must not have been paid for.
+*/
+pub fn release_sale<'info>(
+    escrow: Option<&Account<'info, Escrow>>,
+    paid_price: u64,
+    collection_owner: &AccountInfo<'info>,
+    fee_recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    match escrow {
+        Some(escrow) => release_escrow(escrow, collection_owner, fee_recipient),
+        None => {
+            require!(paid_price == 0, ProtocolError::EscrowRequired);
+            Ok(())
+        }
+    }
+}
+
+
+/*
+    Refunds the price paid minus the cancellation fee to the holder, closing the escrow
+    sends the cancellation fee and the rent to the fee recipient.
+*/
+pub fn cancel_escrow<'info>(
+    escrow: &Account<'info, Escrow>,
+    holder: &AccountInfo<'info>,
+    fee_recipient: &AccountInfo<'info>,
+    cancellation_fee_bps: u16,
+) -> Result<()> {
+    require!(fee_recipient.key() == escrow.fee_recipient, ProtocolError::InvalidFeeRecipient);
+
+    let (refund, _) = cancellation_split(escrow.amount, cancellation_fee_bps)?;
+
+    pay_out(&escrow.to_account_info(), holder, refund)?;
+
+    escrow.close(fee_recipient.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::{ADMIN_FEE, MAX_DELIVERY_WINDOW};
+
+    const AMOUNT: u64 = 1_500_000_000;
+
+    #[test]
+    fn release_pays_the_owner_the_amount_minus_the_fee() {
+        assert_eq!(release_split(AMOUNT, ADMIN_FEE).unwrap(), AMOUNT - ADMIN_FEE);
+        assert_eq!(release_split(AMOUNT, 0).unwrap(), AMOUNT);
+    }
+
+    #[test]
+    fn release_with_fee_equal_to_amount_pays_the_owner_nothing() {
+        assert_eq!(release_split(ADMIN_FEE, ADMIN_FEE).unwrap(), 0);
+    }
+
+    #[test]
+    fn release_rejects_a_fee_above_the_amount() {
+        assert_eq!(release_split(ADMIN_FEE - 1, ADMIN_FEE).unwrap_err(), ProtocolError::InvalidEscrowFee.into());
+    }
+
+    #[test]
+    fn cancellation_without_fee_refunds_everything() {
+        assert_eq!(cancellation_split(AMOUNT, 0).unwrap(), (AMOUNT, 0));
+    }
+
+    #[test]
+    fn cancellation_fee_is_the_bps_share_of_the_amount() {
+        assert_eq!(cancellation_split(AMOUNT, 250).unwrap(), (1_462_500_000, 37_500_000));
+    }
+
+    #[test]
+    fn cancellation_fee_rounds_down_in_favor_of_the_holder() {
+        // 999 * 2.5% = 24.975
+        assert_eq!(cancellation_split(999, 250).unwrap(), (975, 24));
+        // 1 bps of less than 10000 lamports is no fee at all
+        assert_eq!(cancellation_split(9_999, 1).unwrap(), (9_999, 0));
+    }
+
+    #[test]
+    fn cancellation_fee_equal_to_amount_refunds_nothing() {
+        assert_eq!(cancellation_split(AMOUNT, 10_000).unwrap(), (0, AMOUNT));
+        assert_eq!(cancellation_split(u64::MAX, 10_000).unwrap(), (0, u64::MAX));
+    }
+
+    #[test]
+    fn cancellation_split_adds_up_to_the_amount() {
+        for (amount, bps) in [(1, 5_000), (3, 3_333), (u64::MAX, 1), (u64::MAX, 9_999), (123_456_789, 777)] {
+            let (refund, fee) = cancellation_split(amount, bps).unwrap();
+            assert_eq!(refund + fee, amount);
+        }
+    }
+
+    #[test]
+    fn cancellation_rejects_more_than_100_percent() {
+        assert_eq!(cancellation_split(AMOUNT, 10_001).unwrap_err(), ProtocolError::InvalidCancellationFee.into());
+    }
+
+    #[test]
+    fn deadline_is_the_current_time_plus_the_window() {
+        assert_eq!(escrow_deadline(1_000, 60).unwrap(), 1_060);
+        assert_eq!(escrow_deadline(1_700_000_000, MAX_DELIVERY_WINDOW).unwrap(), 1_700_000_000 + MAX_DELIVERY_WINDOW);
+    }
+
+    #[test]
+    fn deadline_overflow_is_an_error_not_a_panic() {
+        assert_eq!(escrow_deadline(i64::MAX - 1, 2).unwrap_err(), ProtocolError::InvalidDeliveryWindow.into());
+    }
+
+    #[test]
+    fn refund_opens_only_after_the_deadline() {
+        assert!(!deadline_passed(999, 1_000));
+        assert!(!deadline_passed(1_000, 1_000));
+        assert!(deadline_passed(1_001, 1_000));
+    }
+}
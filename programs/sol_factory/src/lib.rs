@@ -3,7 +3,19 @@ mod state;
 mod errors;
 mod constant;
 mod context;
+mod sale;
+mod pricing;
+mod escrow;
+mod queue;
+mod metadata;
+mod moderation;
+mod traits;
+mod rarity;
+mod ed25519;
+mod delivery;
+mod placeholder;
 use context::*;
+use state::{CollectionConfig, GenerationStatus, Provenance, TraitDefinition};
 
 declare_id!("E72hAXTsSJn79Xb9mBB7kmK9VoX3HGNaoCyrqEqCE6dd");
 
@@ -19,6 +31,31 @@ pub mod sol_factory {
         ctx.accounts.change_locked_setting()
     }
 
+    pub fn add_moderation_term(ctx: Context<ModerationSetting>, 
+        scope: Pubkey, 
+        term: String
+    ) -> Result<()> {
+        ctx.accounts.add_term(scope, term)
+    }
+
+    pub fn remove_moderation_term(ctx: Context<ModerationRemoval>, 
+        term: String
+    ) -> Result<()> {
+        ctx.accounts.remove_term(term)
+    }
+
+    pub fn set_trait(ctx: Context<TraitSchemaSetting>,
+        definition: TraitDefinition
+    ) -> Result<()> {
+        ctx.accounts.set_trait(definition)
+    }
+
+    pub fn remove_trait(ctx: Context<TraitSchemaRemoval>,
+        key: String
+    ) -> Result<()> {
+        ctx.accounts.remove_trait(key)
+    }
+
     pub fn initialize_admin_account(ctx: Context<AdminInit>, 
         username: String
     ) -> Result<()> {
@@ -39,8 +76,9 @@ pub mod sol_factory {
         max_supply: u64, 
         price: f32, 
         stable_id: String, 
+        config: CollectionConfig,
     ) -> Result<()> {
-        ctx.accounts.create(reference, name, symbol, url, sale_start_time, sale_end_time, max_supply, price, stable_id, ctx.bumps)
+        ctx.accounts.create(reference, name, symbol, url, sale_start_time, sale_end_time, max_supply, price, stable_id, config, ctx.bumps)
     }
 
     pub fn close_collection(ctx: Context<CloseCollection>) -> Result<()> {
@@ -51,15 +89,58 @@ pub mod sol_factory {
         id: u64, 
         uri: String, 
         name: String,  
-        attributes: Vec<Attributes>
+        attributes: Vec<Attributes>,
+        provenance: Provenance,
     ) -> Result<()> {
-        ctx.accounts.create(id, uri, name, attributes, ctx.bumps)
+        ctx.accounts.create(id, uri, name, attributes, provenance, ctx.bumps)
     }
 
     pub fn transfer_nft(ctx: Context<TransferNft>) -> Result<()> {
         ctx.accounts.transfer(ctx.bumps)
     }
 
+    pub fn fulfill(ctx: Context<Fulfill>,
+        uri: String,
+        name: String,
+        attributes: Vec<Attributes>,
+        provenance: Provenance,
+    ) -> Result<()> {
+        ctx.accounts.fulfill(uri, name, attributes, provenance, ctx.bumps)
+    }
+
+    pub fn reveal_placeholder(ctx: Context<RevealPlaceholder>,
+        uri: String,
+        name: String,
+        attributes: Vec<Attributes>,
+        provenance: Provenance,
+    ) -> Result<()> {
+        ctx.accounts.reveal(uri, name, attributes, provenance, ctx.bumps)
+    }
+
+    pub fn update_nft_metadata(ctx: Context<NftMetadataSetting>,
+        uri: Option<String>,
+        name: Option<String>,
+        attributes: Vec<Attributes>,
+    ) -> Result<()> {
+        ctx.accounts.update_metadata(uri, name, attributes, ctx.bumps)
+    }
+
+    pub fn freeze_nft_metadata(ctx: Context<NftMetadataFreeze>) -> Result<()> {
+        ctx.accounts.freeze_metadata(ctx.bumps)
+    }
+
+    pub fn burn_nft(ctx: Context<BurnNft>) -> Result<()> {
+        ctx.accounts.burn_nft(ctx.bumps)
+    }
+
+    pub fn request_reroll(ctx: Context<RequestReroll>) -> Result<()> {
+        ctx.accounts.reroll(ctx.bumps)
+    }
+
+    pub fn rarity_score(ctx: Context<RarityScore>) -> Result<u64> {
+        ctx.accounts.score()
+    }
+
     pub fn create_placeholder(ctx: Context<CreatePlaceholder>, 
         id: u64, 
         uri: String
@@ -67,12 +148,47 @@ pub mod sol_factory {
         ctx.accounts.create(id, uri, ctx.bumps)
     }
 
-    pub fn buy_placeholder(ctx: Context<BuyPlaceholder>) -> Result<()> {
-        ctx.accounts.buy(ctx.bumps)
+    pub fn buy_placeholder(ctx: Context<BuyPlaceholder>, 
+        allowlist_proof: Vec<[u8; 32]>,
+        prompt: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.buy(allowlist_proof, prompt, ctx.bumps)
+    }
+
+    pub fn quote_price(ctx: Context<QuotePrice>) -> Result<u64> {
+        ctx.accounts.quote()
+    }
+
+    pub fn airdrop_placeholder(ctx: Context<AirdropPlaceholder>, expiry: i64, nonce: u64) -> Result<()> {
+        ctx.accounts.airdrop(expiry, nonce, ctx.bumps)
+    }
+
+    pub fn batch_airdrop<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchAirdrop<'info>>,
+        merkle_root: [u8; 32],
+        expiry: i64,
+        nonce: u64,
+        proofs: Vec<Vec<[u8; 32]>>,
+    ) -> Result<u64> {
+        ctx.accounts.batch_airdrop(ctx.remaining_accounts, merkle_root, expiry, nonce, proofs, ctx.bumps)
+    }
+
+    pub fn refund_placeholder(ctx: Context<RefundPlaceholder>) -> Result<()> {
+        ctx.accounts.refund()
+    }
+
+    pub fn update_generation_status(ctx: Context<UpdateGenerationStatus>, 
+        status: GenerationStatus
+    ) -> Result<()> {
+        ctx.accounts.update(status)
+    }
+
+    pub fn cancel_purchase(ctx: Context<CancelPurchase>) -> Result<()> {
+        ctx.accounts.cancel(ctx.bumps)
     }
 
-    pub fn airdrop_placeholder(ctx: Context<AirdropPlaceholder>) -> Result<()> {
-        ctx.accounts.airdrop(ctx.bumps)
+    pub fn claim_generation_request(ctx: Context<ClaimGenerationRequest>) -> Result<()> {
+        ctx.accounts.claim()
     }
 }
 
@@ -0,0 +1,83 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke, invoke_signed},
+        system_instruction,
+    },
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+use spl_token_metadata_interface::{
+    instruction::update_field,
+    state::{Field, TokenMetadata},
+};
+
+// TokenMetadata currently stored on the mint
+pub fn read_metadata(mint: &AccountInfo) -> Result<TokenMetadata> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(state.get_variable_len_extension::<TokenMetadata>()?)
+}
+
+/*
+    Updates a field of the TokenMetadata stored on the mint.
+
+    Token-2022 reallocates the mint to fit the new metadata but doesn't fund it, so the
+    missing rent is sent to the mint by the payer before the update.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn update_metadata_field<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    update_authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    field: Field,
+    value: String,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let new_size = {
+        let mut metadata = read_metadata(mint)?;
+        let old_metadata_size = metadata.tlv_size_of()?;
+        metadata.update(field.clone(), value.clone());
+
+        (mint.data_len() + metadata.tlv_size_of()?).saturating_sub(old_metadata_size)
+    };
+
+    let missing_lamports = Rent::get()?.minimum_balance(new_size).saturating_sub(mint.lamports());
+    if missing_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, mint.key, missing_lamports),
+            &[
+                payer.clone(),
+                mint.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    invoke_signed(
+        &update_field(
+            token_program.key,
+            mint.key,
+            update_authority.key,
+            field,
+            value,
+        ),
+        &[
+            mint.clone(),
+            update_authority.clone(),
+        ],
+        signer_seeds,
+    )?;
+
+    Ok(())
+}
+
+// Lowercase hex encoding used for the hashes stored in the metadata
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::{
+    errors::ModerationError,
+    state::ModerationPolicy,
+};
+
+/*
+    Rejects any text containing a banned term of the passed in policies.
+
+    The policies are passed as their PDA (protocol wide and per collection) so they can't be
+    left out by the caller, a policy that was never initialized has no banned terms.
+*/
+pub fn check_text(policies: &[&AccountInfo], texts: &[&str]) -> Result<()> {
+    for policy in policies.iter() {
+        if policy.data_is_empty() || policy.owner != &crate::ID {
+            continue;
+        }
+
+        let policy = ModerationPolicy::try_deserialize(&mut &policy.try_borrow_data()?[..])?;
+        for text in texts.iter() {
+            let text = text.to_lowercase();
+            require!(
+                !policy.banned.iter().any(|term| text.contains(term.as_str())),
+                ModerationError::BannedText
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke, invoke_signed},
+        system_instruction,
+    },
+};
+use spl_token_2022::{
+    extension::ExtensionType,
+    extension::metadata_pointer::instruction::initialize as initialize_metadata_pointer,
+    extension::group_member_pointer::instruction::initialize as initialize_group_member_pointer,
+    instruction::{initialize_mint_close_authority, initialize_permanent_delegate, initialize_mint2, initialize_non_transferable_mint},
+};
+use spl_token_metadata_interface::{
+    state::{TokenMetadata, Field},
+    instruction::{initialize as initialize_metadata_account, update_field as update_metadata_account},
+};
+use spl_token_group_interface::instruction::initialize_member;
+use crate::state::Collection;
+
+/*
+    Builds the TokenMetadata of a placeholder NFT, the price paid and the buyer prompt are added on buy.
+*/
+pub fn placeholder_metadata(
+    collection: &Account<Collection>,
+    mint: Pubkey,
+    auth: Pubkey,
+    id: u64,
+    count: u64,
+    uri: String,
+) -> Result<TokenMetadata> {
+    Ok(TokenMetadata {
+        update_authority: spl_pod::optional_keys::OptionalNonZeroPubkey::try_from(Some(auth)).unwrap(),
+        mint,
+        name: "Placeholder for".to_string() + &collection.name,
+        symbol: collection.symbol.clone(),
+        uri,
+        additional_metadata: vec![
+            ("id".to_string(), id.to_string()),
+            ("count".to_string(), count.to_string()),
+            ("timestamp".to_string(), Clock::get()?.unix_timestamp.to_string()),
+            ("collection".to_string(), collection.name.to_string()),
+            ("collection key".to_string(), collection.key().to_string())
+        ]
+    })
+}
+
+pub struct PlaceholderMintAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub placeholder: Pubkey,
+    pub mint: AccountInfo<'info>,
+    pub collection_mint: AccountInfo<'info>,
+    pub auth: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+}
+
+/*
+    Creates the Token-2022 mint of a placeholder NFT with its metadata stored on the mint.
+
+    - The auth PDA is the permanent delegate (burns the placeholder on delivery) and the close authority.
+    - In the InPlace reveal mode the mint is also a TokenGroupMember of the collection mint group: the member
+    needs the mint authority, given up once the placeholder is sold, so it's initialized here. Its group
+    member pointer stays empty until the reveal points it to the collection.
+    - The placeholder of a soulbound collection is NonTransferable, it stays with the buyer until delivery.
+*/
+pub fn create_placeholder_mint(accounts: &PlaceholderMintAccounts, metadata: TokenMetadata, reveal_in_place: bool, soulbound: bool, mint_bump: u8, auth_bump: u8) -> Result<()> {
+    // Step 1: Initialize Account
+    let mut extensions = vec![
+        ExtensionType::MintCloseAuthority,
+        ExtensionType::PermanentDelegate,
+        ExtensionType::MetadataPointer,
+    ];
+    if reveal_in_place {
+        extensions.push(ExtensionType::GroupMemberPointer);
+    }
+    if soulbound {
+        extensions.push(ExtensionType::NonTransferable);
+    }
+    let size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions).unwrap();
+
+    // the metadata & the group member are added after the mint is initialized
+    let funded_size = if reveal_in_place {
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+            &[extensions.as_slice(), &[ExtensionType::TokenGroupMember]].concat()
+        ).unwrap()
+    } else {
+        size
+    };
+    let extension_extra_space = metadata.tlv_size_of().unwrap();
+    let rent = &Rent::from_account_info(&accounts.rent)?;
+    let lamports = rent.minimum_balance(funded_size + extension_extra_space);
+
+    let seeds: &[&[u8]; 3] = &[
+        b"mint",
+        accounts.placeholder.as_ref(),
+        &[mint_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    invoke_signed(
+        &system_instruction::create_account(
+            accounts.payer.key,
+            accounts.mint.key,
+            lamports,
+            (size).try_into().unwrap(),
+            &spl_token_2022::id(),
+        ),
+        &[
+            accounts.payer.clone(),
+            accounts.mint.clone(),
+        ],
+        signer_seeds
+    )?;
+
+    // Step 2: Initialize Extension needed:
+
+    // 2.1: Permanent Delegate,
+    invoke(
+        &initialize_permanent_delegate(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            accounts.auth.key,
+        )?,
+        std::slice::from_ref(&accounts.mint),
+    )?;
+
+    // 2.2: Close Mint Authority,
+    invoke(
+        &initialize_mint_close_authority(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            Some(accounts.auth.key),
+        )?,
+        std::slice::from_ref(&accounts.mint),
+    )?;
+
+    // 2.3: Metadata Pointer
+    invoke(
+        &initialize_metadata_pointer(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            Some(accounts.auth.key()),
+            Some(accounts.mint.key()),
+        )?,
+        std::slice::from_ref(&accounts.mint),
+    )?;
+
+    // 2.4: Group Member Pointer, set on reveal
+    if reveal_in_place {
+        invoke(
+            &initialize_group_member_pointer(
+                accounts.token_2022_program.key,
+                accounts.mint.key,
+                Some(accounts.auth.key()),
+                None,
+            )?,
+            std::slice::from_ref(&accounts.mint),
+        )?;
+    }
+
+    // 2.5: Non Transferable, soulbound collections only
+    if soulbound {
+        invoke(
+            &initialize_non_transferable_mint(
+                accounts.token_2022_program.key,
+                accounts.mint.key,
+            )?,
+            std::slice::from_ref(&accounts.mint),
+        )?;
+    }
+
+    // Step 3: Initialize Mint & Metadata Account
+    invoke_signed(
+        &initialize_mint2(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            accounts.auth.key,
+            None,
+            0,
+        )?,
+        std::slice::from_ref(&accounts.mint),
+        signer_seeds
+    )?;
+
+    let seeds: &[&[u8]; 2] = &[
+        b"auth",
+        &[auth_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    invoke_signed(
+        &initialize_metadata_account(
+            accounts.token_2022_program.key,
+            accounts.mint.key,
+            accounts.auth.key,
+            accounts.mint.key,
+            accounts.auth.key,
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        ),
+        &[
+            accounts.mint.clone(),
+            accounts.auth.clone(),
+        ],
+        signer_seeds
+    )?;
+
+    for (field, value) in metadata.additional_metadata.into_iter() {
+        invoke_signed(
+            &update_metadata_account(
+                accounts.token_2022_program.key,
+                accounts.mint.key,
+                accounts.auth.key,
+                Field::Key(field),
+                value,
+            ),
+            &[
+                accounts.mint.clone(),
+                accounts.auth.clone(),
+            ],
+            signer_seeds
+        )?;
+    }
+
+    // Step 4: TokenGroupMember of the collection group, InPlace reveal mode only
+    if reveal_in_place {
+        invoke_signed(
+            &initialize_member(
+                accounts.token_2022_program.key,
+                accounts.mint.key,
+                accounts.mint.key,
+                accounts.auth.key,
+                accounts.collection_mint.key,
+                accounts.auth.key,
+            ),
+            &[
+                accounts.mint.clone(),
+                accounts.auth.clone(),
+                accounts.collection_mint.clone(),
+            ],
+            signer_seeds
+        )?;
+    }
+
+    Ok(())
+}
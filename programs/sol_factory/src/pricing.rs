@@ -0,0 +1,246 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::native_token::LAMPORTS_PER_SOL,
+};
+use crate::{
+    constant::{ADMIN_FEE, BPS_DENOMINATOR},
+    errors::ProtocolError,
+    state::{Collection, PricingMode},
+};
+
+// Fixed point scale used to compound the exponential curve growth rate
+const CURVE_SCALE: u128 = 1_000_000_000_000;
+
+impl PricingMode {
+    pub fn validate(&self, max_supply: u64) -> Result<()> {
+        match *self {
+            PricingMode::Fixed => {}
+            PricingMode::DutchAuction { start_price, floor_price, decay_interval, decay_step } => {
+                // the floor still needs to cover the admin fee
+                require!(floor_price >= ADMIN_FEE, ProtocolError::InvalidPricingMode);
+                require!(start_price >= floor_price, ProtocolError::InvalidPricingMode);
+                require!(decay_interval > 0, ProtocolError::InvalidPricingMode);
+                require!(decay_step > 0, ProtocolError::InvalidPricingMode);
+            }
+            PricingMode::LinearCurve { base_price, increment } => {
+                require!(base_price >= ADMIN_FEE, ProtocolError::InvalidPricingMode);
+                require!(increment > 0, ProtocolError::InvalidPricingMode);
+            }
+            PricingMode::ExponentialCurve { base_price, growth_bps } => {
+                require!(base_price >= ADMIN_FEE, ProtocolError::InvalidPricingMode);
+                require!(growth_bps > 0 && u64::from(growth_bps) <= BPS_DENOMINATOR, ProtocolError::InvalidPricingMode);
+            }
+        }
+
+        // the last mint of the collection must still be priceable
+        if let Some(last_supply) = max_supply.checked_sub(1) {
+            require!(self.curve_price(last_supply).is_ok(), ProtocolError::InvalidPricingMode);
+        }
+
+        Ok(())
+    }
+
+    /*
+        Returns the bonding curve price in lamports for the given supply.
+
+        - Linear: base_price + increment * supply
+        - Exponential: base_price * (1 + growth_bps / 10000) ^ supply
+        - Any other mode doesn't depend on the supply and returns 0.
+    */
+    pub fn curve_price(&self, supply: u64) -> Result<u64> {
+        match *self {
+            PricingMode::LinearCurve { base_price, increment } => {
+                increment
+                    .checked_mul(supply)
+                    .and_then(|step| step.checked_add(base_price))
+                    .ok_or(ProtocolError::PriceOverflow.into())
+            }
+            PricingMode::ExponentialCurve { base_price, growth_bps } => {
+                let mut factor = CURVE_SCALE;
+                let mut rate = CURVE_SCALE * u128::from(u64::from(growth_bps) + BPS_DENOMINATOR) / u128::from(BPS_DENOMINATOR);
+                let mut exponent = supply;
+
+                // square and multiply, so the compute cost only grows with log(supply)
+                while exponent > 0 {
+                    if exponent & 1 == 1 {
+                        factor = factor.checked_mul(rate).ok_or(ProtocolError::PriceOverflow)? / CURVE_SCALE;
+                    }
+                    exponent >>= 1;
+                    if exponent > 0 {
+                        rate = rate.checked_mul(rate).ok_or(ProtocolError::PriceOverflow)? / CURVE_SCALE;
+                    }
+                }
+
+                let price = u128::from(base_price).checked_mul(factor).ok_or(ProtocolError::PriceOverflow)? / CURVE_SCALE;
+                u64::try_from(price).map_err(|_| ProtocolError::PriceOverflow.into())
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+impl Collection {
+    /*
+        Returns the current mint price in lamports.
+
+        - Fixed: the passed in price (active phase or collection price) converted from SOL.
+        - Dutch Auction: start_price minus decay_step for every decay_interval elapsed since
+        the sale start time, never going under the floor_price.
+        - Bonding Curves: the curve price at the current total_supply.
+    */
+    pub fn current_price(&self, now: i64, fixed_price: f32) -> Result<u64> {
+        match self.pricing_mode {
+            PricingMode::Fixed => Ok((fixed_price * LAMPORTS_PER_SOL as f32) as u64),
+            PricingMode::DutchAuction { start_price, floor_price, decay_interval, decay_step } => {
+                let elapsed = now.saturating_sub(self.sale_start_time).max(0);
+                let intervals = (elapsed / decay_interval) as u64;
+                let decay = intervals.saturating_mul(decay_step);
+
+                Ok(start_price.saturating_sub(decay).max(floor_price))
+            }
+            PricingMode::LinearCurve { .. } | PricingMode::ExponentialCurve { .. } => {
+                self.pricing_mode.curve_price(self.total_supply)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::RevealMode;
+
+    const SALE_START: i64 = 1_000;
+
+    fn collection(pricing_mode: PricingMode, total_supply: u64) -> Collection {
+        Collection {
+            reference: Pubkey::default(),
+            name: String::new(),
+            symbol: String::new(),
+            owner: Pubkey::default(),
+            url: String::new(),
+            sale_start_time: SALE_START,
+            sale_end_time: SALE_START + 100_000,
+            max_supply: 100,
+            total_supply,
+            mint_count: 0,
+            price: 1.5,
+            stable_id: String::new(),
+            phases: vec![],
+            max_per_wallet: 0,
+            pricing_mode,
+            delivery_window: 1,
+            cancellation_fee_bps: 0,
+            prompt_enabled: false,
+            max_prompt_len: 0,
+            attestor: Pubkey::default(),
+            airdrop_reserve: 0,
+            airdrop_minted: 0,
+            reveal_mode: RevealMode::Burn,
+            reroll_fee: 0,
+            soulbound: false,
+        }
+    }
+
+    fn dutch_auction() -> PricingMode {
+        PricingMode::DutchAuction {
+            start_price: 10 * ADMIN_FEE,
+            floor_price: 2 * ADMIN_FEE,
+            decay_interval: 60,
+            decay_step: ADMIN_FEE,
+        }
+    }
+
+    #[test]
+    fn fixed_price_is_converted_from_sol() {
+        let collection = collection(PricingMode::Fixed, 0);
+        assert_eq!(collection.current_price(SALE_START, 1.5).unwrap(), 1_500_000_000);
+    }
+
+    #[test]
+    fn dutch_auction_steps_down_per_full_interval() {
+        let collection = collection(dutch_auction(), 0);
+        assert_eq!(collection.current_price(SALE_START, 0.0).unwrap(), 10 * ADMIN_FEE);
+        // an interval only counts once it fully elapsed
+        assert_eq!(collection.current_price(SALE_START + 59, 0.0).unwrap(), 10 * ADMIN_FEE);
+        assert_eq!(collection.current_price(SALE_START + 60, 0.0).unwrap(), 9 * ADMIN_FEE);
+        assert_eq!(collection.current_price(SALE_START + 150, 0.0).unwrap(), 8 * ADMIN_FEE);
+    }
+
+    #[test]
+    fn dutch_auction_stays_at_the_floor() {
+        let collection = collection(dutch_auction(), 0);
+        assert_eq!(collection.current_price(SALE_START + 8 * 60, 0.0).unwrap(), 2 * ADMIN_FEE);
+        assert_eq!(collection.current_price(SALE_START + 60 * 60, 0.0).unwrap(), 2 * ADMIN_FEE);
+        assert_eq!(collection.current_price(i64::MAX, 0.0).unwrap(), 2 * ADMIN_FEE);
+    }
+
+    #[test]
+    fn dutch_auction_before_the_sale_start_is_the_start_price() {
+        let collection = collection(dutch_auction(), 0);
+        assert_eq!(collection.current_price(SALE_START - 1_000, 0.0).unwrap(), 10 * ADMIN_FEE);
+        assert_eq!(collection.current_price(i64::MIN, 0.0).unwrap(), 10 * ADMIN_FEE);
+    }
+
+    #[test]
+    fn linear_curve_grows_with_the_supply() {
+        let mode = PricingMode::LinearCurve { base_price: ADMIN_FEE, increment: 1_000 };
+        assert_eq!(mode.curve_price(0).unwrap(), ADMIN_FEE);
+        assert_eq!(mode.curve_price(3).unwrap(), ADMIN_FEE + 3_000);
+        assert_eq!(collection(mode, 3).current_price(SALE_START, 0.0).unwrap(), ADMIN_FEE + 3_000);
+    }
+
+    #[test]
+    fn linear_curve_overflow_is_an_error() {
+        let mode = PricingMode::LinearCurve { base_price: 1, increment: u64::MAX / 2 };
+        assert_eq!(mode.curve_price(2).unwrap(), u64::MAX);
+        assert!(mode.curve_price(3).is_err());
+
+        let mode = PricingMode::LinearCurve { base_price: u64::MAX, increment: 1 };
+        assert!(mode.curve_price(1).is_err());
+    }
+
+    #[test]
+    fn exponential_curve_compounds_the_growth() {
+        let mode = PricingMode::ExponentialCurve { base_price: ADMIN_FEE, growth_bps: 500 };
+        assert_eq!(mode.curve_price(0).unwrap(), ADMIN_FEE);
+        assert_eq!(mode.curve_price(1).unwrap(), 105_000_000);
+        assert_eq!(mode.curve_price(2).unwrap(), 110_250_000);
+
+        // doubling every mint
+        let mode = PricingMode::ExponentialCurve { base_price: ADMIN_FEE, growth_bps: 10_000 };
+        assert_eq!(mode.curve_price(10).unwrap(), 1_024 * ADMIN_FEE);
+    }
+
+    #[test]
+    fn exponential_curve_rounds_down() {
+        // 1.0001^1 of 3 lamports stays 3
+        let mode = PricingMode::ExponentialCurve { base_price: 3, growth_bps: 1 };
+        assert_eq!(mode.curve_price(1).unwrap(), 3);
+    }
+
+    #[test]
+    fn exponential_curve_overflow_is_an_error() {
+        let mode = PricingMode::ExponentialCurve { base_price: ADMIN_FEE, growth_bps: 10_000 };
+        assert!(mode.curve_price(64).is_err());
+        assert!(mode.curve_price(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_unpriceable_configs() {
+        let floor_under_fee = PricingMode::DutchAuction {
+            start_price: ADMIN_FEE,
+            floor_price: ADMIN_FEE - 1,
+            decay_interval: 60,
+            decay_step: 1,
+        };
+        assert!(floor_under_fee.validate(10).is_err());
+        assert!(dutch_auction().validate(10).is_ok());
+
+        // the last mint of the collection has to be priceable
+        let mode = PricingMode::ExponentialCurve { base_price: ADMIN_FEE, growth_bps: 10_000 };
+        assert!(mode.validate(30).is_ok());
+        assert!(mode.validate(100).is_err());
+        assert!(mode.validate(0).is_ok());
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::{Collection, GenerationQueue, GenerationRequest};
+
+impl GenerationQueue {
+    // Appends a generation request for the placeholder at the tail of the collection queue
+    pub fn enqueue(
+        &mut self,
+        request: &mut GenerationRequest,
+        collection: &Collection,
+        collection_key: Pubkey,
+        placeholder_key: Pubkey,
+        buyer: Pubkey,
+    ) -> Result<()> {
+        self.collection = collection_key;
+
+        *request = GenerationRequest {
+            buyer,
+            placeholder: placeholder_key,
+            collection: collection_key,
+            stable_id: collection.stable_id.clone(),
+            seq: self.tail,
+            time_stamp: Clock::get()?.unix_timestamp,
+            claimed_by: None,
+            cancelled: false,
+        };
+
+        self.tail += 1;
+
+        Ok(())
+    }
+
+    // A cancelled request is completed right away, claiming it later only closes it
+    pub fn cancel(&mut self, request: &mut GenerationRequest) {
+        request.cancelled = true;
+        self.completed += 1;
+    }
+}
@@ -0,0 +1,177 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::hash::hash,
+};
+use crate::{
+    constant::{MAX_TRAIT_COUNTERS, RARITY_SCORE_PRECISION},
+    state::{Attributes, TraitCount, TraitStats},
+};
+
+fn trait_hash(attr: &Attributes) -> Result<[u8; 32]> {
+    Ok(hash(&attr.try_to_vec()?).to_bytes())
+}
+
+impl TraitStats {
+    /*
+        Counts a new AiNft and each of its attributes.
+
+        - Once the counters are full a new trait is not counted, so it never blocks a delivery,
+        and the stats are flagged as overflowed.
+    */
+    pub fn record(&mut self, collection: Pubkey, attributes: &[Attributes]) -> Result<()> {
+        self.collection = collection;
+        self.total += 1;
+
+        for attr in attributes.iter() {
+            let trait_hash = trait_hash(attr)?;
+            match self.counts.iter().position(|counter| counter.trait_hash == trait_hash) {
+                Some(index) => self.counts[index].count += 1,
+                None if self.counts.len() < MAX_TRAIT_COUNTERS => {
+                    self.counts.push(TraitCount { trait_hash, count: 1 });
+                }
+                None => self.overflowed = true,
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+        Uncounts an AiNft that is burned (or whose attributes are about to change), counters
+        dropping to 0 are removed to free their slot, traits that were never counted are skipped.
+    */
+    pub fn erase(&mut self, attributes: &[Attributes]) -> Result<()> {
+        self.total = self.total.saturating_sub(1);
+
+        for attr in attributes.iter() {
+            let trait_hash = trait_hash(attr)?;
+            if let Some(index) = self.counts.iter().position(|counter| counter.trait_hash == trait_hash) {
+                self.counts[index].count = self.counts[index].count.saturating_sub(1);
+                if self.counts[index].count == 0 {
+                    self.counts.swap_remove(index);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+        Rarity score of a set of attributes, the sum of total / count over its traits
+        (the rarer a trait, the higher its part), scaled by RARITY_SCORE_PRECISION.
+    */
+    pub fn rarity_score(&self, attributes: &[Attributes]) -> Result<u64> {
+        let mut score: u128 = 0;
+
+        for attr in attributes.iter() {
+            let trait_hash = trait_hash(attr)?;
+            if let Some(counter) = self.counts.iter().find(|counter| counter.trait_hash == trait_hash) {
+                score += u128::from(self.total) * u128::from(RARITY_SCORE_PRECISION) / u128::from(counter.count);
+            }
+        }
+
+        Ok(u64::try_from(score).unwrap_or(u64::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attr(key: &str, value: &str) -> Attributes {
+        Attributes { key: key.to_string(), value: value.to_string() }
+    }
+
+    fn empty_stats() -> TraitStats {
+        TraitStats { collection: Pubkey::default(), total: 0, counts: vec![], overflowed: false }
+    }
+
+    #[test]
+    fn a_trait_every_nft_has_scores_one() {
+        let mut stats = empty_stats();
+        let attributes = vec![attr("background", "blue"), attr("eyes", "red")];
+        for _ in 0..4 {
+            stats.record(Pubkey::default(), &attributes).unwrap();
+        }
+
+        // count == total, 1.0 per trait
+        assert_eq!(stats.rarity_score(&attributes[..1]).unwrap(), RARITY_SCORE_PRECISION);
+        assert_eq!(stats.rarity_score(&attributes).unwrap(), 2 * RARITY_SCORE_PRECISION);
+    }
+
+    #[test]
+    fn rarer_traits_score_higher() {
+        let mut stats = empty_stats();
+        stats.record(Pubkey::default(), &[attr("background", "gold")]).unwrap();
+        for _ in 0..2 {
+            stats.record(Pubkey::default(), &[attr("background", "blue")]).unwrap();
+        }
+
+        assert_eq!(stats.rarity_score(&[attr("background", "gold")]).unwrap(), 3 * RARITY_SCORE_PRECISION);
+        // 3 / 2 = 1.5
+        assert_eq!(stats.rarity_score(&[attr("background", "blue")]).unwrap(), 1500);
+    }
+
+    #[test]
+    fn score_rounds_down() {
+        let mut stats = empty_stats();
+        stats.record(Pubkey::default(), &[attr("background", "gold")]).unwrap();
+        for _ in 0..2 {
+            stats.record(Pubkey::default(), &[attr("background", "blue")]).unwrap();
+        }
+        for _ in 0..3 {
+            stats.record(Pubkey::default(), &[attr("eyes", "red")]).unwrap();
+        }
+
+        // 6 / 1 + 6 / 3 = 8.0 and 6 / 2 = 3.0
+        assert_eq!(stats.rarity_score(&[attr("background", "gold"), attr("eyes", "red")]).unwrap(), 8 * RARITY_SCORE_PRECISION);
+        assert_eq!(stats.rarity_score(&[attr("background", "blue")]).unwrap(), 3 * RARITY_SCORE_PRECISION);
+        stats.record(Pubkey::default(), &[]).unwrap();
+        // 7 / 3 = 2.333
+        assert_eq!(stats.rarity_score(&[attr("eyes", "red")]).unwrap(), 2333);
+    }
+
+    #[test]
+    fn unknown_traits_and_no_traits_score_zero() {
+        let mut stats = empty_stats();
+        stats.record(Pubkey::default(), &[attr("background", "blue")]).unwrap();
+
+        assert_eq!(stats.rarity_score(&[attr("background", "red")]).unwrap(), 0);
+        assert_eq!(stats.rarity_score(&[attr("Background", "blue")]).unwrap(), 0);
+        assert_eq!(stats.rarity_score(&[]).unwrap(), 0);
+        assert_eq!(empty_stats().rarity_score(&[attr("background", "blue")]).unwrap(), 0);
+    }
+
+    #[test]
+    fn erase_uncounts_and_frees_the_slot() {
+        let mut stats = empty_stats();
+        stats.record(Pubkey::default(), &[attr("background", "blue")]).unwrap();
+        stats.record(Pubkey::default(), &[attr("background", "blue"), attr("eyes", "red")]).unwrap();
+
+        stats.erase(&[attr("background", "blue"), attr("eyes", "red")]).unwrap();
+
+        assert_eq!(stats.total, 1);
+        assert_eq!(stats.counts.len(), 1);
+        assert_eq!(stats.rarity_score(&[attr("background", "blue")]).unwrap(), RARITY_SCORE_PRECISION);
+    }
+
+    #[test]
+    fn full_counters_skip_new_traits() {
+        let mut stats = empty_stats();
+        for index in 0..MAX_TRAIT_COUNTERS {
+            stats.record(Pubkey::default(), &[attr("id", &index.to_string())]).unwrap();
+        }
+        assert!(!stats.overflowed);
+
+        stats.record(Pubkey::default(), &[attr("id", "0"), attr("id", "new")]).unwrap();
+
+        assert!(stats.overflowed);
+        assert_eq!(stats.total, MAX_TRAIT_COUNTERS as u64 + 1);
+        assert_eq!(stats.counts.len(), MAX_TRAIT_COUNTERS);
+        assert_eq!(stats.rarity_score(&[attr("id", "new")]).unwrap(), 0);
+
+        // erasing the uncounted trait leaves the other counters alone
+        stats.erase(&[attr("id", "0"), attr("id", "new")]).unwrap();
+        assert_eq!(stats.counts.len(), MAX_TRAIT_COUNTERS);
+    }
+}
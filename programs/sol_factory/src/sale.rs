@@ -0,0 +1,189 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::keccak::hashv,
+};
+use crate::{
+    constant::MAX_SALE_PHASES,
+    errors::{BuyingError, ProtocolError},
+    state::{Collection, MintRecord, SalePhase},
+};
+
+impl Collection {
+    // Supply available to the public sale & admin airdrops, the airdrop reserve is kept for the owner
+    pub fn sale_supply(&self) -> u64 {
+        self.max_supply.saturating_sub(self.airdrop_reserve)
+    }
+
+    /*
+        Returns the index of the phase that is live at `now`.
+
+        - Collections without phases have no active phase, the collection wide price is used.
+        - Before the first phase (or in between two phases) the sale is NotTimeYet.
+        - After the last phase the sale is Expired.
+    */
+    pub fn active_phase(&self, now: i64) -> Result<Option<usize>> {
+        if self.phases.is_empty() {
+            return Ok(None);
+        }
+
+        match self.phases.iter().position(|phase| now >= phase.start_time && now <= phase.end_time) {
+            Some(index) => Ok(Some(index)),
+            None => {
+                let last_phase_end = self.phases.iter().map(|phase| phase.end_time).max().unwrap_or_default();
+                if now > last_phase_end {
+                    Err(BuyingError::Expired.into())
+                } else {
+                    Err(BuyingError::NotTimeYet.into())
+                }
+            }
+        }
+    }
+
+    // Takes back a refunded sale from the total_supply and the phase it was bought in
+    pub fn unrecord_sale(&mut self, phase: Option<u8>) -> Result<()> {
+        self.total_supply = self.total_supply.checked_sub(1).ok_or(ProtocolError::CounterUnderflow)?;
+
+        if let Some(phase) = phase.and_then(|index| self.phases.get_mut(index as usize)) {
+            phase.minted = phase.minted.checked_sub(1).ok_or(ProtocolError::CounterUnderflow)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MintRecord {
+    // Sets up the record on first use and counts the new mint against the collection wide limit
+    pub fn record_mint(&mut self, collection: &Collection, collection_key: Pubkey, buyer: Pubkey) -> Result<()> {
+        if self.buyer == Pubkey::default() {
+            self.collection = collection_key;
+            self.buyer = buyer;
+        }
+
+        require!(
+            collection.max_per_wallet == 0 || self.minted < collection.max_per_wallet,
+            BuyingError::WalletLimitReached
+        );
+
+        self.minted += 1;
+
+        Ok(())
+    }
+
+    // Gives a refunded mint back to the wallet, for the collection wide limit and the phase it was bought in
+    pub fn unrecord_mint(&mut self, phase: Option<u8>) -> Result<()> {
+        self.minted = self.minted.checked_sub(1).ok_or(ProtocolError::CounterUnderflow)?;
+
+        if let Some(phase_minted) = phase.and_then(|index| self.phase_minted.get_mut(index as usize)) {
+            *phase_minted = phase_minted.checked_sub(1).ok_or(ProtocolError::CounterUnderflow)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Phases need to be sorted, non overlapping and inside of the collection sale window
+pub fn validate_phases(
+    phases: &[SalePhase],
+    sale_start_time: i64,
+    sale_end_time: i64,
+    max_supply: u64,
+) -> Result<()> {
+    require!(phases.len() <= MAX_SALE_PHASES, ProtocolError::InvalidSalePhase);
+
+    let mut previous_end = sale_start_time - 1;
+    for phase in phases.iter() {
+        require!(phase.start_time < phase.end_time, ProtocolError::InvalidSalePhase);
+        require!(phase.start_time > previous_end, ProtocolError::InvalidSalePhase);
+        require!(phase.end_time <= sale_end_time, ProtocolError::InvalidSalePhase);
+        require!(phase.price >= 0.0, ProtocolError::InvalidPrice);
+        require!(phase.supply_cap <= max_supply, ProtocolError::InvalidSalePhase);
+        previous_end = phase.end_time;
+    }
+
+    Ok(())
+}
+
+// Allowlist leaves are keccak(wallet)
+pub fn verify_allowlist_proof(proof: &[[u8; 32]], merkle_root: &[u8; 32], wallet: &Pubkey) -> bool {
+    verify_merkle_proof(proof, merkle_root, hashv(&[wallet.as_ref()]).0)
+}
+
+// Pairs are hashed in sorted order
+pub fn verify_merkle_proof(proof: &[[u8; 32]], merkle_root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed_root = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).0
+        } else {
+            hashv(&[sibling, &node]).0
+        }
+    });
+
+    computed_root == *merkle_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(wallet: &Pubkey) -> [u8; 32] {
+        hashv(&[wallet.as_ref()]).0
+    }
+
+    fn parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        if a <= b { hashv(&[a, b]).0 } else { hashv(&[b, a]).0 }
+    }
+
+    // 4 wallets allowlist: root = H(H(l0, l1), H(l2, l3))
+    fn tree() -> ([Pubkey; 4], [[u8; 32]; 4], [u8; 32]) {
+        let wallets = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let leaves = wallets.map(|wallet| leaf(&wallet));
+        let root = parent(&parent(&leaves[0], &leaves[1]), &parent(&leaves[2], &leaves[3]));
+        (wallets, leaves, root)
+    }
+
+    #[test]
+    fn valid_proofs() {
+        let (wallets, leaves, root) = tree();
+        let left = parent(&leaves[0], &leaves[1]);
+        let right = parent(&leaves[2], &leaves[3]);
+
+        assert!(verify_allowlist_proof(&[leaves[1], right], &root, &wallets[0]));
+        assert!(verify_allowlist_proof(&[leaves[0], right], &root, &wallets[1]));
+        assert!(verify_allowlist_proof(&[leaves[3], left], &root, &wallets[2]));
+        assert!(verify_allowlist_proof(&[leaves[2], left], &root, &wallets[3]));
+    }
+
+    #[test]
+    fn bad_proof_ordering() {
+        let (wallets, leaves, root) = tree();
+        let right = parent(&leaves[2], &leaves[3]);
+
+        assert!(!verify_allowlist_proof(&[right, leaves[1]], &root, &wallets[0]));
+    }
+
+    #[test]
+    fn wrong_sibling_or_leaf() {
+        let (wallets, leaves, root) = tree();
+        let right = parent(&leaves[2], &leaves[3]);
+
+        assert!(!verify_allowlist_proof(&[leaves[2], right], &root, &wallets[0]));
+        assert!(!verify_allowlist_proof(&[leaves[1], right], &root, &Pubkey::new_unique()));
+        assert!(!verify_merkle_proof(&[leaves[1], right], &root, wallets[0].to_bytes()));
+    }
+
+    #[test]
+    fn empty_proof() {
+        let (wallets, leaves, root) = tree();
+
+        assert!(!verify_allowlist_proof(&[], &root, &wallets[0]));
+        // single wallet allowlist, the root is the leaf itself
+        assert!(verify_allowlist_proof(&[], &leaves[0], &wallets[0]));
+    }
+
+    #[test]
+    fn truncated_proof() {
+        let (wallets, leaves, root) = tree();
+
+        assert!(!verify_allowlist_proof(&[leaves[1]], &root, &wallets[0]));
+    }
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::constant::{MAX_SALE_PHASES, MAX_BANNED_TERMS, MAX_BANNED_TERM_LEN, MAX_SCHEMA_TRAITS, MAX_TRAIT_KEY_LEN, MAX_TRAIT_VALUES, MAX_TRAIT_VALUE_LEN, MAX_TRAIT_COUNTERS};
 
 // Setup State
 #[account]
@@ -21,6 +22,23 @@ impl Space for Admin {
     const INIT_SPACE: usize = 8 + 32 + 4 + 8;
 }
 
+// Optional collection settings of create_collection, grouped so a new option is a new field
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct CollectionConfig {
+    pub phases: Vec<SalePhase>,
+    pub max_per_wallet: u64,
+    pub pricing_mode: PricingMode,
+    pub delivery_window: i64,
+    pub cancellation_fee_bps: u16,
+    pub prompt_enabled: bool,
+    pub max_prompt_len: u16,
+    pub attestor: Pubkey,
+    pub airdrop_reserve: u64,
+    pub reveal_mode: RevealMode,
+    pub reroll_fee: u64,
+    pub soulbound: bool,
+}
+
 #[account]
 pub struct Collection {
     pub reference: Pubkey,
@@ -35,10 +53,90 @@ pub struct Collection {
     pub mint_count: u64,
     pub price: f32,
     pub stable_id: String,
+    pub phases: Vec<SalePhase>,
+    pub max_per_wallet: u64, // 0 = no per wallet limit
+    pub pricing_mode: PricingMode,
+    pub delivery_window: i64, // seconds the AiNft has to be delivered in before the buyer can refund
+    pub cancellation_fee_bps: u16, // kept from the refund when a buyer cancels before generation starts
+    pub prompt_enabled: bool, // buyers can pass a prompt to the generation
+    pub max_prompt_len: u16,
+    pub attestor: Pubkey, // generation backend key that has to sign every AiNft of the collection
+    pub airdrop_reserve: u64, // part of the max_supply only the collection owner can airdrop
+    pub airdrop_minted: u64, // owner airdrops, not counted in the total_supply
+    pub reveal_mode: RevealMode,
+    pub reroll_fee: u64, // lamports charged to burn a delivered AiNft for a new generation, 0 = no rerolls
+    pub soulbound: bool, // placeholder & AiNft mints are NonTransferable
 }
 
 impl Space for Collection {
-    const INIT_SPACE: usize = 8 + 32 + 4 + 4 + 32 + 4 + 8 + 8 + 8 + 8 + 8 + 4 + 4 + 8 + 8; 
+    const INIT_SPACE: usize = 8 + 32 + 4 + 4 + 32 + 4 + 8 + 8 + 8 + 8 + 8 + 4 + 4 + 8 + 8 + 4 + 8 + PricingMode::INIT_SPACE + 8 + 2 + 1 + 2 + 32 + 8 + 8 + 1 + 8 + 1; 
+}
+
+// Prices of the non Fixed modes are in lamports
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq)]
+pub enum PricingMode {
+    Fixed,
+    DutchAuction { start_price: u64, floor_price: u64, decay_interval: i64, decay_step: u64 },
+    LinearCurve { base_price: u64, increment: u64 },
+    ExponentialCurve { base_price: u64, growth_bps: u16 },
+}
+
+impl Space for PricingMode {
+    const INIT_SPACE: usize = 1 + 8 + 8 + 8 + 8;
+}
+
+// How a placeholder becomes its AiNft
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq)]
+pub enum RevealMode {
+    Burn, // a new AiNft mint is created and the placeholder burned (create_nft + transfer_nft or fulfill)
+    InPlace, // the placeholder mint itself is rewritten into the AiNft (reveal_placeholder)
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq)]
+pub enum PhaseGate {
+    Public,
+    Allowlist { merkle_root: [u8; 32] },
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct SalePhase {
+    pub name: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub price: f32,
+    pub supply_cap: u64, // 0 = no cap besides the collection max_supply
+    pub max_per_wallet: u64, // 0 = no per wallet limit
+    pub gate: PhaseGate,
+    pub minted: u64,
+}
+
+impl Space for SalePhase {
+    const INIT_SPACE: usize = 4 + 8 + 8 + 4 + 8 + 8 + 1 + 32 + 8;
+}
+
+#[account]
+pub struct MintRecord {
+    pub collection: Pubkey,
+    pub buyer: Pubkey,
+    pub minted: u64,
+    pub phase_minted: [u64; MAX_SALE_PHASES],
+}
+
+impl Space for MintRecord {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 8 * MAX_SALE_PHASES;
+}
+
+// Marks an airdrop voucher nonce as redeemed
+#[account]
+pub struct Voucher {
+    pub nonce: u64,
+    pub collection: Pubkey,
+    pub buyer: Pubkey,
+    pub used: bool,
+}
+
+impl Space for Voucher {
+    const INIT_SPACE: usize = 8 + 8 + 32 + 32 + 1;
 }
 
 #[account]
@@ -47,12 +145,126 @@ pub struct Placeholder {
     pub collection: Pubkey,
     pub reference: String,
     pub name: String,
-    pub price: f32,
     pub time_stamp: i64,
+    pub paid_price: u64, // lamports actually paid on mint
+    pub status: GenerationStatus,
+    pub prompt: String, // set by the buyer, up to collection.max_prompt_len
+    pub rerolls: u32, // number of times the AiNft of this serial was burned for a new generation
+    pub uri: String, // uri of the placeholder mint metadata, reissued as is on reroll
+    pub count: u64, // collection count when the placeholder was created, reissued as is on reroll
 }
 
 impl Space for Placeholder {
-    const INIT_SPACE: usize = 8 + 8 + 32 + 4 + 2 + 2 + 8 + 8;
+    // discriminator, id, collection, reference (base58 pubkey), name prefix, time_stamp, paid_price,
+    // status, prompt prefix, rerolls, uri prefix, count
+    const INIT_SPACE: usize = 8 + 8 + 32 + 4 + 44 + 4 + 8 + 8 + 1 + 4 + 4 + 4 + 8;
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GenerationStatus {
+    Queued,
+    Generating,
+    Delivered,
+}
+
+#[account]
+pub struct Escrow {
+    pub placeholder: Pubkey,
+    pub buyer: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub amount: u64, // lamports held, admin fee included
+    pub fee: u64,
+    pub deadline: i64, // after this time the holder can refund the placeholder
+    pub phase: Option<u8>, // sale phase of the buy, its counters are decreased on refund
+    pub reroll: bool, // reroll fee, the serial was already counted in the supply by its sale
+}
+
+impl Space for Escrow {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 2 + 1;
+}
+
+#[account]
+pub struct GenerationQueue {
+    pub collection: Pubkey,
+    pub head: u64, // sequence number of the next request to claim
+    pub tail: u64, // sequence number of the next request to enqueue
+    pub completed: u64, // delivered or cancelled requests, outstanding work = tail - completed
+}
+
+impl Space for GenerationQueue {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8 + 8;
+}
+
+#[account]
+#[derive(Default)]
+pub struct GenerationRequest {
+    pub buyer: Pubkey,
+    pub placeholder: Pubkey,
+    pub collection: Pubkey,
+    pub stable_id: String,
+    pub seq: u64,
+    pub time_stamp: i64,
+    pub claimed_by: Option<Pubkey>,
+    pub cancelled: bool,
+}
+
+impl Space for GenerationRequest {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 32 + 4 + 8 + 8 + 33 + 1;
+}
+
+#[account]
+pub struct ModerationPolicy {
+    pub scope: Pubkey, // protocol account for the protocol wide policy, collection for its extension
+    pub banned: Vec<String>, // lowercase substrings
+}
+
+impl Space for ModerationPolicy {
+    const INIT_SPACE: usize = 8 + 32 + 4 + MAX_BANNED_TERMS * (4 + MAX_BANNED_TERM_LEN);
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq)]
+pub enum TraitRule {
+    MaxLen { max_len: u16 }, // any value up to max_len bytes
+    OneOf { values: Vec<String> }, // up to MAX_TRAIT_VALUES enumerated values
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct TraitDefinition {
+    pub key: String,
+    pub required: bool,
+    pub rule: TraitRule,
+}
+
+impl Space for TraitDefinition {
+    const INIT_SPACE: usize = 4 + MAX_TRAIT_KEY_LEN + 1 + 1 + 4 + MAX_TRAIT_VALUES * (4 + MAX_TRAIT_VALUE_LEN);
+}
+
+#[account]
+pub struct TraitSchema {
+    pub collection: Pubkey,
+    pub traits: Vec<TraitDefinition>, // up to MAX_SCHEMA_TRAITS
+}
+
+impl Space for TraitSchema {
+    const INIT_SPACE: usize = 8 + 32 + 4 + MAX_SCHEMA_TRAITS * TraitDefinition::INIT_SPACE;
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct TraitCount {
+    pub trait_hash: [u8; 32], // sha256 of the borsh serialized attribute (key & value)
+    pub count: u64,
+}
+
+#[account]
+pub struct TraitStats {
+    pub collection: Pubkey,
+    pub total: u64, // AiNfts counted
+    pub counts: Vec<TraitCount>, // up to MAX_TRAIT_COUNTERS
+    pub overflowed: bool, // a new trait found the counters full and was not counted
+}
+
+impl Space for TraitStats {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 4 + MAX_TRAIT_COUNTERS * (32 + 8) + 1;
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
@@ -61,15 +273,46 @@ pub struct Attributes {
     pub value: String,
 }
 
+impl Space for Attributes {
+    const INIT_SPACE: usize = 4 + 4; // key & value length prefixes
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct Provenance {
+    pub model: String, // must match the collection stable_id
+    pub seed: u64,
+    pub prompt_hash: [u8; 32], // sha256 of the prompt used for the generation
+    pub image_hash: [u8; 32], // sha256 of the image bytes
+}
+
 #[account]
 pub struct AiNft {
     pub id: u64,
     pub collection: Pubkey,
     pub reference: String,
-    pub price: f32,
+    pub price: u64, // lamports paid for the placeholder
     pub time_stamp: i64,
+    pub model: String,
+    pub seed: u64,
+    pub prompt_hash: [u8; 32],
+    pub image_hash: [u8; 32],
+    pub mint: Pubkey, // the placeholder mint for InPlace reveals
+    pub revision: u32, // number of metadata updates
+    pub frozen: bool, // metadata can't be updated anymore
+    pub rerolls: u32, // rerolls of the serial before this generation
+    pub attributes: Vec<Attributes>, // up to MAX_NFT_ATTRIBUTES, provenance keys excluded
 }
 
 impl Space for AiNft {
-    const INIT_SPACE: usize = 8 + 8 + 32 + 4 + 2 + 32 + 2 + 8;
+    // discriminator, id, collection, reference (base58 pubkey), price, time_stamp, model prefix, seed,
+    // prompt_hash, image_hash, mint, revision, frozen, rerolls, attributes prefix
+    const INIT_SPACE: usize = 8 + 8 + 32 + 4 + 44 + 8 + 8 + 4 + 8 + 32 + 32 + 32 + 4 + 1 + 4 + 4;
+}
+
+impl AiNft {
+    pub fn space(model: &str, attributes: &[Attributes]) -> usize {
+        AiNft::INIT_SPACE
+            + model.len()
+            + attributes.iter().map(|attr| Attributes::INIT_SPACE + attr.key.len() + attr.value.len()).sum::<usize>()
+    }
 }
\ No newline at end of file
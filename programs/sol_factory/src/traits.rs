@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::{
+    errors::TraitSchemaError,
+    state::{Attributes, TraitRule, TraitSchema},
+};
+
+/*
+    Rejects attributes that don't conform to the collection trait schema.
+
+    The schema is passed as its PDA so it can't be left out by the caller, a collection
+    that never defined a schema (or whose schema has no trait left) accepts any attributes.
+    - Every key must be defined in the schema and used once.
+    - Every value must be one of the enumerated values or fit the max length.
+    - Every required trait must be present.
+*/
+pub fn check_attributes(schema: &AccountInfo, attributes: &[Attributes]) -> Result<()> {
+    if schema.data_is_empty() || schema.owner != &crate::ID {
+        return Ok(());
+    }
+
+    let schema = TraitSchema::try_deserialize(&mut &schema.try_borrow_data()?[..])?;
+    if schema.traits.is_empty() {
+        return Ok(());
+    }
+
+    for (index, attr) in attributes.iter().enumerate() {
+        require!(
+            !attributes[..index].iter().any(|previous| previous.key == attr.key),
+            TraitSchemaError::DuplicateTrait
+        );
+
+        let definition = schema.traits.iter()
+            .find(|definition| definition.key == attr.key)
+            .ok_or(TraitSchemaError::UnknownTrait)?;
+        let valid = match &definition.rule {
+            TraitRule::MaxLen { max_len } => attr.value.len() <= *max_len as usize,
+            TraitRule::OneOf { values } => values.contains(&attr.value),
+        };
+        require!(valid, TraitSchemaError::InvalidTraitValue);
+    }
+
+    require!(
+        schema.traits.iter()
+            .filter(|definition| definition.required)
+            .all(|definition| attributes.iter().any(|attr| attr.key == definition.key)),
+        TraitSchemaError::MissingRequiredTrait
+    );
+
+    Ok(())
+}